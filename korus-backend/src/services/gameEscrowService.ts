@@ -10,6 +10,13 @@ import {
 import { logger } from '../utils/logger';
 import prisma from '../config/database';
 
+// Anchor reads the program ID in an optional account's slot as "not provided"
+const NO_ACCOUNT = { pubkey: GAME_ESCROW_PROGRAM_ID, isSigner: false, isWritable: false };
+
+// SOL games leave out every account of the TokenEscrow group:
+// mint, vault, treasury / player1 / player2 token accounts, token program
+const NO_TOKEN_ESCROW = Array(6).fill(NO_ACCOUNT);
+
 export class GameEscrowService {
   /**
    * Verify that a game was created on-chain by checking if the game PDA exists
@@ -211,7 +218,7 @@ export class GameEscrowService {
       logger.info(`Player2 State PDA: ${player2StatePda.toString()}`);
 
       // Build complete_game instruction manually
-      // Account order must match smart contract: state, game, player1State, player2State, escrow, treasury, player1, player2, authority, systemProgram
+      // Account order must match smart contract: state, game, player1State, player2State, escrow, treasury, player1, player2, authority, tokenEscrow
       const instruction = {
         programId: GAME_ESCROW_PROGRAM_ID,
         keys: [
@@ -223,8 +230,8 @@ export class GameEscrowService {
           { pubkey: TREASURY_WALLET, isSigner: false, isWritable: true }, // treasury
           { pubkey: player1, isSigner: false, isWritable: true }, // player1
           { pubkey: player2, isSigner: false, isWritable: true }, // player2
-          { pubkey: authority.publicKey, isSigner: true, isWritable: false }, // authority
-          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // system_program
+          { pubkey: authority.publicKey, isSigner: true, isWritable: true }, // authority
+          ...NO_TOKEN_ESCROW, // token_escrow
         ],
         data: this.encodeCompleteGameInstruction(gameId, winnerAddress),
       };
@@ -404,19 +411,28 @@ export class GameEscrowService {
 
       logger.info(`Player1 State PDA: ${player1StatePda.toString()}`);
 
+      // Expiry is measured against the game type's waiting_expiry_seconds
+      const gameType = gameAccount.data.readUInt8(16);
+      const [gameTypeConfigPda] = await PublicKey.findProgramAddress(
+        [Buffer.from('game_type'), Buffer.from([gameType])],
+        GAME_ESCROW_PROGRAM_ID
+      );
+
       // Build authority_cancel_expired_game instruction manually
       // Account order must match smart contract AuthorityCancelExpiredGame context:
-      // state, game, player_state, escrow, player1, authority, system_program
+      // state, game, game_type_config, player_state, escrow, player1, authority, system_program, token_escrow
       const instruction = {
         programId: GAME_ESCROW_PROGRAM_ID,
         keys: [
           { pubkey: statePda, isSigner: false, isWritable: true }, // state
           { pubkey: gamePda, isSigner: false, isWritable: true }, // game
+          { pubkey: gameTypeConfigPda, isSigner: false, isWritable: false }, // game_type_config
           { pubkey: player1StatePda, isSigner: false, isWritable: true }, // player_state
           { pubkey: escrowPda, isSigner: false, isWritable: true }, // escrow
           { pubkey: player1, isSigner: false, isWritable: true }, // player1 (does NOT need to sign!)
           { pubkey: authority.publicKey, isSigner: true, isWritable: false }, // authority
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // system_program
+          ...NO_TOKEN_ESCROW, // token_escrow
         ],
         data: this.encodeAuthorityCancelExpiredGameInstruction(),
       };
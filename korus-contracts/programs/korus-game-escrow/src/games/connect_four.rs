@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+
+use super::{GameRules, Move, MoveContext, Outcome};
+use crate::ErrorCode;
+
+const COLUMNS: usize = 7;
+const ROWS: usize = 6;
+
+// 7x6 board: cell = row * 7 + column, row 0 is the bottom row
+pub struct ConnectFour;
+
+impl ConnectFour {
+    pub const STATE_SIZE: usize = COLUMNS * ROWS;
+}
+
+impl GameRules for ConnectFour {
+    fn max_state_size(&self) -> usize {
+        Self::STATE_SIZE
+    }
+
+    fn apply_move(&self, state: &mut [u8], ctx: &MoveContext, mv: &Move) -> Result<u8> {
+        let Move::Column(column) = *mv else {
+            return err!(ErrorCode::UnsupportedGameType);
+        };
+        require!((column as usize) < COLUMNS, ErrorCode::InvalidMove);
+
+        // Gravity: the piece lands in the lowest empty row of the column
+        let cell = (0..ROWS)
+            .map(|row| row * COLUMNS + column as usize)
+            .find(|&cell| state[cell] == 0)
+            .ok_or(ErrorCode::ColumnFull)?;
+
+        state[cell] = ctx.seat;
+        Ok(cell as u8)
+    }
+
+    fn outcome(&self, state: &[u8]) -> Outcome {
+        let at = |row: usize, column: usize| state[row * COLUMNS + column];
+
+        // Horizontal, vertical and both diagonals, anchored at every start cell
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let seat = at(row, column);
+                if seat == 0 {
+                    continue;
+                }
+                for (d_row, d_column) in [(0i32, 1i32), (1, 0), (1, 1), (1, -1)] {
+                    let four = (1..4).all(|i| {
+                        let r = row as i32 + d_row * i;
+                        let c = column as i32 + d_column * i;
+                        (0..ROWS as i32).contains(&r)
+                            && (0..COLUMNS as i32).contains(&c)
+                            && at(r as usize, c as usize) == seat
+                    });
+                    if four {
+                        return Outcome::Won(seat);
+                    }
+                }
+            }
+        }
+
+        if state[..Self::STATE_SIZE].iter().all(|&cell| cell != 0) {
+            Outcome::Draw
+        } else {
+            Outcome::InProgress
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::Seat;
+
+    fn drop_piece(state: &mut [u8], seat: Seat, column: u8) -> Result<u8> {
+        let ctx = MoveContext {
+            seat,
+            player: Pubkey::default(),
+            now: 0,
+            move_timeout: 0,
+        };
+        ConnectFour.apply_move(state, &ctx, &Move::Column(column))
+    }
+
+    fn wins(cells: [(usize, usize); 4]) -> bool {
+        let mut state = [0; ConnectFour::STATE_SIZE];
+        for (row, column) in cells {
+            state[row * COLUMNS + column] = 1;
+        }
+        matches!(ConnectFour.outcome(&state), Outcome::Won(1))
+    }
+
+    #[test]
+    fn four_in_a_row_wins_in_every_direction() {
+        assert!(wins([(0, 3), (0, 4), (0, 5), (0, 6)]));
+        assert!(wins([(2, 0), (3, 0), (4, 0), (5, 0)]));
+        assert!(wins([(0, 0), (1, 1), (2, 2), (3, 3)]));
+        assert!(wins([(2, 6), (3, 5), (4, 4), (5, 3)]));
+        assert!(!wins([(0, 4), (0, 5), (0, 6), (1, 0)]));
+    }
+
+    #[test]
+    fn pieces_stack_until_the_column_is_full() {
+        let mut state = [0; ConnectFour::STATE_SIZE];
+        for row in 0..ROWS {
+            let seat = (row % 2) as Seat + 1;
+            assert_eq!(
+                drop_piece(&mut state, seat, 2).unwrap() as usize,
+                row * COLUMNS + 2
+            );
+        }
+        assert_eq!(
+            drop_piece(&mut state, 1, 2).unwrap_err(),
+            ErrorCode::ColumnFull.into()
+        );
+        assert_eq!(
+            drop_piece(&mut state, 1, COLUMNS as u8).unwrap_err(),
+            ErrorCode::InvalidMove.into()
+        );
+    }
+
+    #[test]
+    fn full_board_without_four_is_a_draw() {
+        let mut state = [0; ConnectFour::STATE_SIZE];
+        for row in 0..ROWS {
+            let pattern = if row % 2 == 1 {
+                [2, 2, 1, 1, 2, 2, 1]
+            } else {
+                [1, 1, 2, 2, 1, 1, 2]
+            };
+            state[row * COLUMNS..(row + 1) * COLUMNS].copy_from_slice(&pattern);
+        }
+        assert!(matches!(ConnectFour.outcome(&state), Outcome::Draw));
+        state[ConnectFour::STATE_SIZE - 1] = 0;
+        assert!(matches!(ConnectFour.outcome(&state), Outcome::InProgress));
+    }
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::{ErrorCode, GAME_STATE_SIZE};
+
+mod connect_four;
+mod rock_paper_scissors;
+mod tic_tac_toe;
+
+pub use connect_four::ConnectFour;
+pub use rock_paper_scissors::RockPaperScissors;
+pub use tic_tac_toe::TicTacToe;
+
+// Every game's state must fit in Game.state
+const _: () = assert!(TicTacToe::STATE_SIZE <= GAME_STATE_SIZE);
+const _: () = assert!(ConnectFour::STATE_SIZE <= GAME_STATE_SIZE);
+const _: () = assert!(RockPaperScissors::STATE_SIZE <= GAME_STATE_SIZE);
+
+// Seat 1 is player1, seat 2 is player2 (also the mark stored on boards)
+pub type Seat = u8;
+
+// Discriminants are the game_type bytes clients send (see the client's gameTypeMap)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GameType {
+    TicTacToe,
+    RockPaperScissors,
+    ConnectFour,
+}
+
+impl GameType {
+    pub fn rules(self) -> &'static dyn GameRules {
+        match self {
+            GameType::TicTacToe => &TicTacToe,
+            GameType::RockPaperScissors => &RockPaperScissors,
+            GameType::ConnectFour => &ConnectFour,
+        }
+    }
+}
+
+impl TryFrom<u8> for GameType {
+    type Error = Error;
+
+    fn try_from(game_type: u8) -> Result<Self> {
+        match game_type {
+            0 => Ok(GameType::TicTacToe),
+            1 => Ok(GameType::RockPaperScissors),
+            2 => Ok(GameType::ConnectFour),
+            _ => err!(ErrorCode::UnsupportedGameType),
+        }
+    }
+}
+
+// Moves accepted by the move instructions; each game only accepts its own kind
+pub enum Move {
+    Cell(u8),
+    Column(u8),
+    Commit([u8; 32]),
+    Reveal { choice: u8, salt: [u8; 32] },
+}
+
+pub struct MoveContext {
+    pub seat: Seat,
+    pub player: Pubkey,
+    pub now: i64,
+    pub move_timeout: i64,
+}
+
+pub struct TurnClock {
+    pub to_move: Seat,
+    pub last_move_time: i64,
+    pub now: i64,
+    pub move_timeout: i64,
+}
+
+pub enum Outcome {
+    InProgress,
+    Won(Seat),
+    Draw,
+}
+
+pub trait GameRules {
+    // Bytes of Game.state this game uses
+    fn max_state_size(&self) -> usize;
+
+    // Resets the state for a new round
+    fn init_state(&self, state: &mut [u8]) {
+        state[..self.max_state_size()].fill(0);
+    }
+
+    // Validates and applies a move, returning the state cell it wrote (reported in events)
+    fn apply_move(&self, state: &mut [u8], ctx: &MoveContext, mv: &Move) -> Result<u8>;
+
+    fn outcome(&self, state: &[u8]) -> Outcome;
+
+    // Turn-based games only accept moves from Game.current_turn and pass it after each move
+    fn is_turn_based(&self) -> bool {
+        true
+    }
+
+    // Whether a drawn round is always replayed instead of ending a single-round game
+    fn replays_draws(&self) -> bool {
+        false
+    }
+
+    // Seat that wins a stalled game (None splits the pot)
+    fn timeout_winner(&self, _state: &[u8], clock: &TurnClock) -> Result<Option<Seat>> {
+        turn_timeout_winner(clock)
+    }
+}
+
+// The player whose turn it is forfeits after State.move_timeout_seconds
+pub fn turn_timeout_winner(clock: &TurnClock) -> Result<Option<Seat>> {
+    require!(
+        clock.now - clock.last_move_time > clock.move_timeout,
+        ErrorCode::TimeoutNotReached
+    );
+    Ok(Some(opponent(clock.to_move)))
+}
+
+pub fn opponent(seat: Seat) -> Seat {
+    3 - seat
+}
@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use super::{GameRules, Move, MoveContext, Outcome, Seat, TurnClock};
+use crate::ErrorCode;
+
+// Choices (0 means not revealed yet)
+const ROCK: u8 = 1;
+const SCISSORS: u8 = 3;
+
+// State layout: commitments (32 bytes per seat), revealed choices (1 byte per
+// seat), reveal deadline (i64 LE)
+const COMMITMENTS: usize = 0;
+const CHOICES: usize = 64;
+const REVEAL_DEADLINE: usize = 66;
+
+// Simultaneous commit-reveal round. Commitment = sha256(choice || salt || player),
+// so a player can't copy the opponent's hash and reveal it.
+pub struct RockPaperScissors;
+
+impl RockPaperScissors {
+    pub const STATE_SIZE: usize = REVEAL_DEADLINE + 8;
+}
+
+fn commitment(state: &[u8], seat: Seat) -> &[u8] {
+    let start = COMMITMENTS + 32 * (seat as usize - 1);
+    &state[start..start + 32]
+}
+
+fn is_committed(state: &[u8], seat: Seat) -> bool {
+    commitment(state, seat) != [0; 32]
+}
+
+fn choice(state: &[u8], seat: Seat) -> u8 {
+    state[CHOICES + seat as usize - 1]
+}
+
+fn reveal_deadline(state: &[u8]) -> i64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&state[REVEAL_DEADLINE..REVEAL_DEADLINE + 8]);
+    i64::from_le_bytes(bytes)
+}
+
+impl GameRules for RockPaperScissors {
+    fn max_state_size(&self) -> usize {
+        Self::STATE_SIZE
+    }
+
+    fn apply_move(&self, state: &mut [u8], ctx: &MoveContext, mv: &Move) -> Result<u8> {
+        match *mv {
+            Move::Commit(hash) => {
+                require!(!is_committed(state, ctx.seat), ErrorCode::AlreadyCommitted);
+                require!(hash != [0; 32], ErrorCode::InvalidCommitment);
+                let start = COMMITMENTS + 32 * (ctx.seat as usize - 1);
+                state[start..start + 32].copy_from_slice(&hash);
+
+                // Reveal window opens once both players are locked in
+                if is_committed(state, 1) && is_committed(state, 2) {
+                    let deadline = ctx.now + ctx.move_timeout;
+                    state[REVEAL_DEADLINE..REVEAL_DEADLINE + 8]
+                        .copy_from_slice(&deadline.to_le_bytes());
+                }
+            }
+            Move::Reveal { choice: revealed, salt } => {
+                require!(
+                    is_committed(state, 1) && is_committed(state, 2),
+                    ErrorCode::CommitmentsPending
+                );
+                require!(
+                    ctx.now <= reveal_deadline(state),
+                    ErrorCode::RevealDeadlinePassed
+                );
+                require!((ROCK..=SCISSORS).contains(&revealed), ErrorCode::InvalidMove);
+                require!(choice(state, ctx.seat) == 0, ErrorCode::AlreadyRevealed);
+
+                let expected = hashv(&[&[revealed], &salt, ctx.player.as_ref()]).to_bytes();
+                require!(
+                    expected == commitment(state, ctx.seat),
+                    ErrorCode::RevealMismatch
+                );
+                state[CHOICES + ctx.seat as usize - 1] = revealed;
+            }
+            _ => return err!(ErrorCode::UnsupportedGameType),
+        }
+        Ok(ctx.seat)
+    }
+
+    // Rock (1) beats scissors (3), paper (2) beats rock, scissors beats paper
+    fn outcome(&self, state: &[u8]) -> Outcome {
+        let (choice1, choice2) = (choice(state, 1), choice(state, 2));
+        if choice1 == 0 || choice2 == 0 {
+            return Outcome::InProgress;
+        }
+        match (3 + choice1 - choice2) % 3 {
+            0 => Outcome::Draw,
+            1 => Outcome::Won(1),
+            _ => Outcome::Won(2),
+        }
+    }
+
+    fn is_turn_based(&self) -> bool {
+        false
+    }
+
+    fn replays_draws(&self) -> bool {
+        true
+    }
+
+    // The player who stalled the commit or reveal phase forfeits; the pot is
+    // split if neither player committed (or neither revealed) in time
+    fn timeout_winner(&self, state: &[u8], clock: &TurnClock) -> Result<Option<Seat>> {
+        let committed = (is_committed(state, 1), is_committed(state, 2));
+        if committed == (true, true) {
+            require!(
+                clock.now > reveal_deadline(state),
+                ErrorCode::TimeoutNotReached
+            );
+            return Ok(match (choice(state, 1) != 0, choice(state, 2) != 0) {
+                (true, false) => Some(1),
+                (false, true) => Some(2),
+                _ => None,
+            });
+        }
+
+        require!(
+            clock.now - clock.last_move_time > clock.move_timeout,
+            ErrorCode::TimeoutNotReached
+        );
+        Ok(match committed {
+            (true, false) => Some(1),
+            (false, true) => Some(2),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAPER: u8 = 2;
+    const MOVE_TIMEOUT: i64 = 60;
+
+    struct Round {
+        state: [u8; RockPaperScissors::STATE_SIZE],
+        players: [Pubkey; 2],
+    }
+
+    impl Round {
+        fn new() -> Self {
+            Self {
+                state: [0; RockPaperScissors::STATE_SIZE],
+                players: [Pubkey::new_unique(), Pubkey::new_unique()],
+            }
+        }
+
+        fn play(&mut self, seat: Seat, now: i64, mv: Move) -> Result<u8> {
+            let ctx = MoveContext {
+                seat,
+                player: self.players[seat as usize - 1],
+                now,
+                move_timeout: MOVE_TIMEOUT,
+            };
+            RockPaperScissors.apply_move(&mut self.state, &ctx, &mv)
+        }
+
+        fn commit(&mut self, seat: Seat, choice: u8) {
+            let player = self.players[seat as usize - 1];
+            let hash = hashv(&[&[choice], &[seat; 32], player.as_ref()]).to_bytes();
+            self.play(seat, 0, Move::Commit(hash)).unwrap();
+        }
+
+        fn reveal(&mut self, seat: Seat, choice: u8, now: i64) -> Result<u8> {
+            let salt = [seat; 32];
+            self.play(seat, now, Move::Reveal { choice, salt })
+        }
+
+        fn timeout_winner(&self, now: i64) -> Result<Option<Seat>> {
+            let clock = TurnClock {
+                to_move: 1,
+                last_move_time: 0,
+                now,
+                move_timeout: MOVE_TIMEOUT,
+            };
+            RockPaperScissors.timeout_winner(&self.state, &clock)
+        }
+    }
+
+    fn outcome(choice1: u8, choice2: u8) -> Outcome {
+        let mut round = Round::new();
+        round.commit(1, choice1);
+        round.commit(2, choice2);
+        round.reveal(1, choice1, 0).unwrap();
+        round.reveal(2, choice2, 0).unwrap();
+        RockPaperScissors.outcome(&round.state)
+    }
+
+    #[test]
+    fn every_pairing_has_the_expected_outcome() {
+        for choice in ROCK..=SCISSORS {
+            assert!(matches!(outcome(choice, choice), Outcome::Draw));
+        }
+        for (winner, loser) in [(ROCK, SCISSORS), (PAPER, ROCK), (SCISSORS, PAPER)] {
+            assert!(matches!(outcome(winner, loser), Outcome::Won(1)));
+            assert!(matches!(outcome(loser, winner), Outcome::Won(2)));
+        }
+    }
+
+    #[test]
+    fn reveals_must_match_the_commitment() {
+        let mut round = Round::new();
+        round.commit(1, ROCK);
+        round.commit(2, PAPER);
+
+        assert_eq!(
+            round.reveal(1, PAPER, 0).unwrap_err(),
+            ErrorCode::RevealMismatch.into()
+        );
+        let salt = [9; 32];
+        assert_eq!(
+            round
+                .play(1, 0, Move::Reveal { choice: ROCK, salt })
+                .unwrap_err(),
+            ErrorCode::RevealMismatch.into()
+        );
+        // Another player's commitment can't be revealed as one's own
+        round.players.swap(0, 1);
+        assert_eq!(
+            round.reveal(1, ROCK, 0).unwrap_err(),
+            ErrorCode::RevealMismatch.into()
+        );
+        round.players.swap(0, 1);
+
+        round.reveal(1, ROCK, 0).unwrap();
+        assert_eq!(
+            round.reveal(1, ROCK, 0).unwrap_err(),
+            ErrorCode::AlreadyRevealed.into()
+        );
+    }
+
+    #[test]
+    fn reveals_wait_for_both_commitments() {
+        let mut round = Round::new();
+        round.commit(1, ROCK);
+        assert_eq!(
+            round.reveal(1, ROCK, 0).unwrap_err(),
+            ErrorCode::CommitmentsPending.into()
+        );
+        assert_eq!(
+            round.play(1, 0, Move::Commit([1; 32])).unwrap_err(),
+            ErrorCode::AlreadyCommitted.into()
+        );
+    }
+
+    #[test]
+    fn missing_the_reveal_deadline_forfeits() {
+        let mut round = Round::new();
+        round.commit(1, ROCK);
+        round.commit(2, PAPER);
+        round.reveal(1, ROCK, MOVE_TIMEOUT).unwrap();
+
+        assert_eq!(
+            round.reveal(2, PAPER, MOVE_TIMEOUT + 1).unwrap_err(),
+            ErrorCode::RevealDeadlinePassed.into()
+        );
+        assert_eq!(
+            round.timeout_winner(MOVE_TIMEOUT).unwrap_err(),
+            ErrorCode::TimeoutNotReached.into()
+        );
+        assert_eq!(round.timeout_winner(MOVE_TIMEOUT + 1).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn nobody_wins_when_neither_player_reveals() {
+        let mut round = Round::new();
+        round.commit(1, ROCK);
+        round.commit(2, PAPER);
+        assert_eq!(round.timeout_winner(MOVE_TIMEOUT + 1).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_the_commit_forfeits() {
+        let mut round = Round::new();
+        round.commit(2, PAPER);
+        assert_eq!(
+            round.timeout_winner(MOVE_TIMEOUT).unwrap_err(),
+            ErrorCode::TimeoutNotReached.into()
+        );
+        assert_eq!(round.timeout_winner(MOVE_TIMEOUT + 1).unwrap(), Some(2));
+    }
+}
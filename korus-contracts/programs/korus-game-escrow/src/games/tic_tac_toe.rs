@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use super::{GameRules, Move, MoveContext, Outcome};
+use crate::ErrorCode;
+
+// 3x3 board, row-major: 0 empty, 1 player1 (X), 2 player2 (O)
+pub struct TicTacToe;
+
+impl TicTacToe {
+    pub const STATE_SIZE: usize = 9;
+}
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+impl GameRules for TicTacToe {
+    fn max_state_size(&self) -> usize {
+        Self::STATE_SIZE
+    }
+
+    fn apply_move(&self, state: &mut [u8], ctx: &MoveContext, mv: &Move) -> Result<u8> {
+        let Move::Cell(cell) = *mv else {
+            return err!(ErrorCode::UnsupportedGameType);
+        };
+        require!((cell as usize) < Self::STATE_SIZE, ErrorCode::InvalidMove);
+        require!(state[cell as usize] == 0, ErrorCode::CellOccupied);
+
+        state[cell as usize] = ctx.seat;
+        Ok(cell)
+    }
+
+    fn outcome(&self, state: &[u8]) -> Outcome {
+        for [a, b, c] in LINES {
+            if state[a] != 0 && state[a] == state[b] && state[a] == state[c] {
+                return Outcome::Won(state[a]);
+            }
+        }
+        if state[..Self::STATE_SIZE].iter().all(|&cell| cell != 0) {
+            Outcome::Draw
+        } else {
+            Outcome::InProgress
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::Seat;
+
+    fn place(state: &mut [u8], seat: Seat, cell: u8) -> Result<u8> {
+        let ctx = MoveContext {
+            seat,
+            player: Pubkey::default(),
+            now: 0,
+            move_timeout: 0,
+        };
+        TicTacToe.apply_move(state, &ctx, &Move::Cell(cell))
+    }
+
+    #[test]
+    fn every_line_wins() {
+        for line in LINES {
+            let mut state = [0; TicTacToe::STATE_SIZE];
+            for cell in line {
+                state[cell] = 2;
+            }
+            assert!(matches!(TicTacToe.outcome(&state), Outcome::Won(2)));
+        }
+    }
+
+    #[test]
+    fn full_board_without_a_line_is_a_draw() {
+        let mut state = [1, 2, 1, 1, 2, 2, 2, 1, 1];
+        assert!(matches!(TicTacToe.outcome(&state), Outcome::Draw));
+        state[8] = 0;
+        assert!(matches!(TicTacToe.outcome(&state), Outcome::InProgress));
+    }
+
+    #[test]
+    fn occupied_cells_are_rejected() {
+        let mut state = [0; TicTacToe::STATE_SIZE];
+        assert_eq!(place(&mut state, 1, 4).unwrap(), 4);
+        assert_eq!(
+            place(&mut state, 2, 4).unwrap_err(),
+            ErrorCode::CellOccupied.into()
+        );
+        assert_eq!(state[4], 1);
+    }
+
+    #[test]
+    fn cells_off_the_board_are_rejected() {
+        let mut state = [0; TicTacToe::STATE_SIZE];
+        assert_eq!(
+            place(&mut state, 1, 9).unwrap_err(),
+            ErrorCode::InvalidMove.into()
+        );
+    }
+}
@@ -1,12 +1,72 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::Discriminator;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount},
+    extension::{BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+mod games;
+mod signatures;
+mod tournament;
+
+use games::{GameType, Move, MoveContext, Outcome, Seat, TurnClock};
+pub use tournament::*;
 
 declare_id!("4iUdAkPRmZLzUFXTLpt5QPGmUUtP6yfgpPpF3sLD9xtd");
 
-const PLATFORM_FEE_BPS: u16 = 200; // 2% platform fee
-const MINIMUM_WAGER: u64 = 10_000_000; // 0.01 SOL minimum
-const MAXIMUM_WAGER: u64 = 1_000_000_000; // 1 SOL maximum
-const MOVE_TIMEOUT_SECONDS: i64 = 600; // 10 minutes per move
+// Defaults for the admin-configurable values in State
+const DEFAULT_PLATFORM_FEE_BPS: u16 = 200; // 2% platform fee
+const DEFAULT_MINIMUM_WAGER: u64 = 10_000_000; // 0.01 SOL minimum
+const DEFAULT_MAXIMUM_WAGER: u64 = 1_000_000_000; // 1 SOL maximum
+const DEFAULT_MOVE_TIMEOUT_SECONDS: i64 = 600; // 10 minutes per move
+const DEFAULT_BET_FEE_BPS: u16 = 200; // 2% of spectator bet pools
+
+// Bounds enforced by update_config
+const MAX_PLATFORM_FEE_BPS: u16 = 1000; // 10% max
+const MIN_WAGER_FLOOR: u64 = 1_000_000; // 0.001 SOL, keeps the escrow above rent-exemption
+const MAX_WAGER_CEILING: u64 = 100_000_000_000; // 100 SOL
+const MIN_MOVE_TIMEOUT_SECONDS: i64 = 60; // 1 minute
+const MAX_MOVE_TIMEOUT_SECONDS: i64 = 86_400; // 24 hours
+
+// How long a game may wait for player2 before the authority can cancel it
+const DEFAULT_WAITING_EXPIRY_SECONDS: i64 = 7_200; // 2 hours, matches the backend
+const MIN_WAITING_EXPIRY_SECONDS: i64 = 300; // 5 minutes
+const MAX_WAITING_EXPIRY_SECONDS: i64 = 604_800; // 7 days
+
+// Per-round game state, sized for the largest GameRules::max_state_size()
+const GAME_STATE_SIZE: usize = 74;
+
+// Concurrent games per player: default and the most a PlayerState can hold
+const DEFAULT_MAX_ACTIVE_GAMES: u8 = 1;
+const MAX_ACTIVE_GAMES_CAP: u8 = 10;
+
+// Best-of-N series: first to rounds_to_win takes the pot
+const MAX_ROUNDS_TO_WIN: u8 = 5;
+
+// State.paused bits, one per instruction class. Cancels, refunds and account
+// closing can't be paused so funds are never trapped.
+const PAUSE_CREATE: u8 = 1 << 0; // create_game, create_game_with_deposit, tournament matches
+const PAUSE_JOIN: u8 = 1 << 1; // join_game, register_for_tournament
+const PAUSE_PLAY: u8 = 1 << 2; // moves, resign, offers and update_move_time; stops move clocks
+const PAUSE_SETTLE: u8 = 1 << 3; // complete_game, record_tournament_match
+const PAUSE_BETS: u8 = 1 << 4; // place_bet
+const PAUSE_ALL: u8 = PAUSE_CREATE | PAUSE_JOIN | PAUSE_PLAY | PAUSE_SETTLE | PAUSE_BETS;
+
+// Domain tags of the messages signed for settle_by_consent and settle_with_voucher
+const CONSENT_DOMAIN: &[u8] = b"korus-consent";
+const VOUCHER_DOMAIN: &[u8] = b"korus-voucher";
+
+// Longest dispute window the authority can configure
+const MAX_DISPUTE_WINDOW_SECONDS: i64 = 604_800; // 7 days
+
+// Most oracles an OracleSet (and so a game's attestation record) can hold
+const MAX_ORACLES: usize = 10;
 
 #[program]
 pub mod korus_game_escrow {
@@ -18,37 +78,433 @@ pub mod korus_game_escrow {
         state.treasury = treasury;
         state.total_games = 0;
         state.total_volume = 0;
-        state.platform_fee_bps = PLATFORM_FEE_BPS;
+        state.platform_fee_bps = DEFAULT_PLATFORM_FEE_BPS;
         state.active_games = 0;
+        state.max_active_games_per_player = DEFAULT_MAX_ACTIVE_GAMES;
+        state.minimum_wager = DEFAULT_MINIMUM_WAGER;
+        state.maximum_wager = DEFAULT_MAXIMUM_WAGER;
+        state.move_timeout_seconds = DEFAULT_MOVE_TIMEOUT_SECONDS;
+        state.pending_authority = Pubkey::default();
+        state.paused = 0;
+        state.play_paused_at = 0;
+        state.play_paused_seconds = 0;
+        state.oracle_min_wager = u64::MAX;
+        state.arbitrator = Pubkey::default();
+        state.dispute_window_seconds = 0;
+        state.result_signer = Pubkey::default();
+        state.total_tournaments = 0;
+        state.bet_fee_bps = DEFAULT_BET_FEE_BPS;
+        Ok(())
+    }
+
+    // Key the backend signs result vouchers with; default disables vouchers
+    pub fn set_result_signer(ctx: Context<UpdateConfig>, result_signer: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let old_result_signer = state.result_signer;
+        state.result_signer = result_signer;
+
+        emit!(ResultSignerUpdated {
+            old_result_signer,
+            new_result_signer: result_signer,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_bet_fee(ctx: Context<UpdateConfig>, bet_fee_bps: u16) -> Result<()> {
+        require!(bet_fee_bps <= MAX_PLATFORM_FEE_BPS, ErrorCode::FeeTooHigh);
+
+        let state = &mut ctx.accounts.state;
+        let old_fee_bps = state.bet_fee_bps;
+        state.bet_fee_bps = bet_fee_bps;
+
+        emit!(BetFeeUpdated {
+            old_fee_bps,
+            new_fee_bps: bet_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_pause(ctx: Context<UpdateConfig>, paused: u8) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        let state = &mut ctx.accounts.state;
+        let old_paused = state.paused;
+        state.paused = paused;
+
+        // Move clocks stop while play is paused; the time is banked on resume
+        let now = Clock::get()?.unix_timestamp;
+        match (old_paused & PAUSE_PLAY != 0, paused & PAUSE_PLAY != 0) {
+            (false, true) => state.play_paused_at = now,
+            (true, false) => {
+                state.play_paused_seconds += now - state.play_paused_at;
+                state.play_paused_at = 0;
+            }
+            _ => {}
+        }
+
+        emit!(PauseUpdated {
+            old_paused,
+            new_paused: paused,
+        });
+
+        Ok(())
+    }
+
+    // Games wagering at least min_wager can only be settled by `threshold`
+    // matching submit_result attestations from the oracle set
+    pub fn set_oracle_set(
+        ctx: Context<SetOracleSet>,
+        oracles: Vec<Pubkey>,
+        threshold: u8,
+        min_wager: u64,
+    ) -> Result<()> {
+        require!(
+            !oracles.is_empty() && oracles.len() <= MAX_ORACLES,
+            ErrorCode::InvalidOracleSet
+        );
+        require!(
+            (1..=oracles.len()).contains(&(threshold as usize)),
+            ErrorCode::InvalidOracleThreshold
+        );
+        for (i, oracle) in oracles.iter().enumerate() {
+            require!(
+                *oracle != Pubkey::default() && !oracles[..i].contains(oracle),
+                ErrorCode::InvalidOracleSet
+            );
+        }
+
+        let oracle_set = &mut ctx.accounts.oracle_set;
+        oracle_set.oracles = oracles;
+        oracle_set.threshold = threshold;
+        ctx.accounts.state.oracle_min_wager = min_wager;
+
+        emit!(OracleSetUpdated {
+            oracles: oracle_set.oracles.clone(),
+            threshold,
+            min_wager,
+        });
+
+        Ok(())
+    }
+
+    // With a non-zero window, authority verdicts go through propose_result and
+    // can be disputed to the arbitrator before they pay out. Disputed games
+    // wait on the arbitrator even after the window is turned off, so once set
+    // it can be replaced but never cleared.
+    pub fn set_dispute_config(
+        ctx: Context<UpdateConfig>,
+        arbitrator: Pubkey,
+        dispute_window_seconds: i64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(
+            (0..=MAX_DISPUTE_WINDOW_SECONDS).contains(&dispute_window_seconds),
+            ErrorCode::InvalidDisputeWindow
+        );
+        require!(
+            arbitrator != Pubkey::default()
+                || (dispute_window_seconds == 0 && state.arbitrator == Pubkey::default()),
+            ErrorCode::InvalidArbitrator
+        );
+
+        state.arbitrator = arbitrator;
+        state.dispute_window_seconds = dispute_window_seconds;
+
+        emit!(DisputeConfigUpdated {
+            arbitrator,
+            dispute_window_seconds,
+        });
+
+        Ok(())
+    }
+
+    // Fees are sent to the treasury with system transfers, so it must be a
+    // system-owned wallet
+    pub fn update_treasury(ctx: Context<UpdateTreasury>) -> Result<()> {
+        // Fees are often below rent-exemption, and transfers that would leave
+        // an unfunded treasury rent-paying fail, blocking every settlement
+        require!(
+            ctx.accounts.new_treasury.lamports() >= Rent::get()?.minimum_balance(0),
+            ErrorCode::TreasuryNotRentExempt
+        );
+
+        let state = &mut ctx.accounts.state;
+        let old_treasury = state.treasury;
+        state.treasury = ctx.accounts.new_treasury.key();
+
+        emit!(TreasuryUpdated {
+            old_treasury,
+            new_treasury: state.treasury,
+        });
+
+        Ok(())
+    }
+
+    // First step of an authority transfer; nothing changes until the new key
+    // signs accept_authority
+    pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        require!(
+            new_authority != Pubkey::default(),
+            ErrorCode::InvalidAuthority
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.pending_authority = new_authority;
+
+        emit!(AuthorityTransferProposed {
+            authority: state.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let old_authority = state.authority;
+        state.authority = state.pending_authority;
+        state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            old_authority,
+            new_authority: state.authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_authority_transfer(ctx: Context<UpdateConfig>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(
+            state.pending_authority != Pubkey::default(),
+            ErrorCode::NoPendingAuthority
+        );
+        let pending_authority = state.pending_authority;
+        state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferCancelled {
+            authority: state.authority,
+            pending_authority,
+        });
+
+        Ok(())
+    }
+
+    // Global defaults that new game type configs start from
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        platform_fee_bps: u16,
+        minimum_wager: u64,
+        maximum_wager: u64,
+        move_timeout_seconds: i64,
+    ) -> Result<()> {
+        validate_config(
+            platform_fee_bps,
+            minimum_wager,
+            maximum_wager,
+            move_timeout_seconds,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        let event = ConfigUpdated {
+            old_platform_fee_bps: state.platform_fee_bps,
+            new_platform_fee_bps: platform_fee_bps,
+            old_minimum_wager: state.minimum_wager,
+            new_minimum_wager: minimum_wager,
+            old_maximum_wager: state.maximum_wager,
+            new_maximum_wager: maximum_wager,
+            old_move_timeout_seconds: state.move_timeout_seconds,
+            new_move_timeout_seconds: move_timeout_seconds,
+        };
+
+        state.platform_fee_bps = platform_fee_bps;
+        state.minimum_wager = minimum_wager;
+        state.maximum_wager = maximum_wager;
+        state.move_timeout_seconds = move_timeout_seconds;
+
+        emit!(event);
+
+        Ok(())
+    }
+
+    // Creates the config for a game type from the global State values; game
+    // instructions for that type need it to exist
+    pub fn init_game_type_config(
+        ctx: Context<InitGameTypeConfig>,
+        game_type: GameType,
+    ) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let config = &mut ctx.accounts.game_type_config;
+        config.game_type = game_type;
+        config.platform_fee_bps = state.platform_fee_bps;
+        config.minimum_wager = state.minimum_wager;
+        config.maximum_wager = state.maximum_wager;
+        config.move_timeout_seconds = state.move_timeout_seconds;
+        config.waiting_expiry_seconds = DEFAULT_WAITING_EXPIRY_SECONDS;
+        config.enabled = true;
+
+        emit!(GameTypeConfigUpdated {
+            game_type,
+            platform_fee_bps: config.platform_fee_bps,
+            minimum_wager: config.minimum_wager,
+            maximum_wager: config.maximum_wager,
+            move_timeout_seconds: config.move_timeout_seconds,
+            waiting_expiry_seconds: config.waiting_expiry_seconds,
+            enabled: config.enabled,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_game_type_config(
+        ctx: Context<UpdateGameTypeConfig>,
+        _game_type: GameType,
+        platform_fee_bps: u16,
+        minimum_wager: u64,
+        maximum_wager: u64,
+        move_timeout_seconds: i64,
+        waiting_expiry_seconds: i64,
+    ) -> Result<()> {
+        validate_config(
+            platform_fee_bps,
+            minimum_wager,
+            maximum_wager,
+            move_timeout_seconds,
+        )?;
+        require!(
+            (MIN_WAITING_EXPIRY_SECONDS..=MAX_WAITING_EXPIRY_SECONDS)
+                .contains(&waiting_expiry_seconds),
+            ErrorCode::InvalidWaitingExpiry
+        );
+
+        let config = &mut ctx.accounts.game_type_config;
+        config.platform_fee_bps = platform_fee_bps;
+        config.minimum_wager = minimum_wager;
+        config.maximum_wager = maximum_wager;
+        config.move_timeout_seconds = move_timeout_seconds;
+        config.waiting_expiry_seconds = waiting_expiry_seconds;
+
+        emit!(GameTypeConfigUpdated {
+            game_type: config.game_type,
+            platform_fee_bps,
+            minimum_wager,
+            maximum_wager,
+            move_timeout_seconds,
+            waiting_expiry_seconds,
+            enabled: config.enabled,
+        });
+
+        Ok(())
+    }
+
+    // Disabling a game type only stops new games; games in play finish normally
+    pub fn set_game_type_enabled(
+        ctx: Context<UpdateGameTypeConfig>,
+        _game_type: GameType,
+        enabled: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.game_type_config;
+        config.enabled = enabled;
+
+        emit!(GameTypeConfigUpdated {
+            game_type: config.game_type,
+            platform_fee_bps: config.platform_fee_bps,
+            minimum_wager: config.minimum_wager,
+            maximum_wager: config.maximum_wager,
+            move_timeout_seconds: config.move_timeout_seconds,
+            waiting_expiry_seconds: config.waiting_expiry_seconds,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_max_active_games(ctx: Context<UpdateConfig>, limit: u8) -> Result<()> {
+        require!(
+            (1..=MAX_ACTIVE_GAMES_CAP).contains(&limit),
+            ErrorCode::InvalidActiveGameLimit
+        );
+
+        let state = &mut ctx.accounts.state;
+        let old_limit = state.max_active_games_per_player;
+        state.max_active_games_per_player = limit;
+
+        emit!(ActiveGameLimitUpdated {
+            old_limit,
+            new_limit: limit,
+        });
+
+        Ok(())
+    }
+
+    // Sets the wager bounds for games in `mint`; a mint must be enabled here
+    // before create_token_game accepts it
+    pub fn set_mint_config(
+        ctx: Context<SetMintConfig>,
+        minimum_wager: u64,
+        maximum_wager: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        require!(
+            minimum_wager > 0 && minimum_wager <= maximum_wager,
+            ErrorCode::InvalidWagerLimits
+        );
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.mint = ctx.accounts.mint.key();
+        mint_config.minimum_wager = minimum_wager;
+        mint_config.maximum_wager = maximum_wager;
+        mint_config.enabled = enabled;
+
+        emit!(MintConfigUpdated {
+            mint: mint_config.mint,
+            minimum_wager,
+            maximum_wager,
+            enabled,
+        });
+
         Ok(())
     }
 
     pub fn create_game(
         ctx: Context<CreateGame>,
-        game_type: u8,
+        game_type: GameType,
         wager_amount: u64,
+        rounds_to_win: u8,
+        off_chain_rules: bool,
+        invited_player: Option<Pubkey>,
+        join_key: Option<Pubkey>,
     ) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_CREATE)?;
+        let config = &ctx.accounts.game_type_config;
+        require!(config.enabled, ErrorCode::GameTypeDisabled);
         require!(
-            wager_amount >= MINIMUM_WAGER && wager_amount <= MAXIMUM_WAGER,
+            config.wager_in_range(wager_amount),
             ErrorCode::InvalidWagerAmount
         );
+        require!(
+            (1..=MAX_ROUNDS_TO_WIN).contains(&rounds_to_win),
+            ErrorCode::InvalidRoundsToWin
+        );
 
         let state = &mut ctx.accounts.state;
         let game = &mut ctx.accounts.game;
         let clock = Clock::get()?;
 
-        game.game_id = state.total_games;
-        game.game_type = game_type;
-        game.player1 = ctx.accounts.player1.key();
-        game.player2 = Pubkey::default();
-        game.wager_amount = wager_amount;
-        game.status = 0; // Waiting
-        game.winner = Pubkey::default();
-        game.player1_deposited = wager_amount;
-        game.player2_deposited = 0;
-        game.created_at = clock.unix_timestamp;
-        game.last_move_time = clock.unix_timestamp;
-        game.current_turn = ctx.accounts.player1.key();
+        game.set_inner(Game::init(
+            state.total_games,
+            game_type,
+            ctx.accounts.player1.key(),
+            wager_amount,
+            rounds_to_win,
+            off_chain_rules,
+            Pubkey::default(),
+            config.platform_fee_bps,
+            clock.unix_timestamp,
+        ));
+        game.set_invitation(invited_player, join_key)?;
 
         // NO CPI! The transfer happens directly in the transaction
         // The escrow account receives SOL via the transaction's SystemProgram.transfer
@@ -62,7 +518,7 @@ pub mod korus_game_escrow {
         // Update player state to track game
         let player_state = &mut ctx.accounts.player_state;
         player_state.player = ctx.accounts.player1.key();
-        player_state.current_game_id = Some(state.total_games);
+        player_state.add_game(state.total_games, state.max_active_games_per_player)?;
 
         state.total_games += 1;
         state.total_volume += wager_amount;
@@ -73,6 +529,10 @@ pub mod korus_game_escrow {
             player1: game.player1,
             wager_amount,
             game_type,
+            rounds_to_win,
+            off_chain_rules,
+            mint: game.mint,
+            invited_player,
         });
 
         Ok(())
@@ -80,30 +540,41 @@ pub mod korus_game_escrow {
 
     pub fn create_game_with_deposit(
         ctx: Context<CreateGameWithDeposit>,
-        game_type: u8,
+        game_type: GameType,
         wager_amount: u64,
+        rounds_to_win: u8,
+        off_chain_rules: bool,
+        invited_player: Option<Pubkey>,
+        join_key: Option<Pubkey>,
     ) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_CREATE)?;
+        let config = &ctx.accounts.game_type_config;
+        require!(config.enabled, ErrorCode::GameTypeDisabled);
         require!(
-            wager_amount >= MINIMUM_WAGER && wager_amount <= MAXIMUM_WAGER,
+            config.wager_in_range(wager_amount),
             ErrorCode::InvalidWagerAmount
         );
+        require!(
+            (1..=MAX_ROUNDS_TO_WIN).contains(&rounds_to_win),
+            ErrorCode::InvalidRoundsToWin
+        );
 
         let state = &mut ctx.accounts.state;
         let game = &mut ctx.accounts.game;
         let clock = Clock::get()?;
 
-        game.game_id = state.total_games;
-        game.game_type = game_type;
-        game.player1 = ctx.accounts.player1.key();
-        game.player2 = Pubkey::default();
-        game.wager_amount = wager_amount;
-        game.status = 0; // Waiting
-        game.winner = Pubkey::default();
-        game.player1_deposited = wager_amount;
-        game.player2_deposited = 0;
-        game.created_at = clock.unix_timestamp;
-        game.last_move_time = clock.unix_timestamp;
-        game.current_turn = ctx.accounts.player1.key();
+        game.set_inner(Game::init(
+            state.total_games,
+            game_type,
+            ctx.accounts.player1.key(),
+            wager_amount,
+            rounds_to_win,
+            off_chain_rules,
+            Pubkey::default(),
+            config.platform_fee_bps,
+            clock.unix_timestamp,
+        ));
+        game.set_invitation(invited_player, join_key)?;
 
         // Transfer wager from player to escrow (AFTER accounts are initialized)
         let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
@@ -122,7 +593,7 @@ pub mod korus_game_escrow {
         // Update player state to track game
         let player_state = &mut ctx.accounts.player_state;
         player_state.player = ctx.accounts.player1.key();
-        player_state.current_game_id = Some(state.total_games);
+        player_state.add_game(state.total_games, state.max_active_games_per_player)?;
 
         state.total_games += 1;
         state.total_volume += wager_amount;
@@ -133,45 +604,149 @@ pub mod korus_game_escrow {
             player1: game.player1,
             wager_amount,
             game_type,
+            rounds_to_win,
+            off_chain_rules,
+            mint: game.mint,
+            invited_player,
+        });
+
+        Ok(())
+    }
+
+    // Like create_game_with_deposit, but the wager is in an SPL token held by
+    // a vault owned by the game's escrow PDA
+    pub fn create_token_game(
+        ctx: Context<CreateTokenGame>,
+        game_type: GameType,
+        wager_amount: u64,
+        rounds_to_win: u8,
+        off_chain_rules: bool,
+        invited_player: Option<Pubkey>,
+        join_key: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_CREATE)?;
+        require!(ctx.accounts.game_type_config.enabled, ErrorCode::GameTypeDisabled);
+        let mint_config = &ctx.accounts.mint_config;
+        require!(mint_config.enabled, ErrorCode::MintDisabled);
+        require!(
+            mint_config.wager_in_range(wager_amount),
+            ErrorCode::InvalidWagerAmount
+        );
+        require!(
+            (1..=MAX_ROUNDS_TO_WIN).contains(&rounds_to_win),
+            ErrorCode::InvalidRoundsToWin
+        );
+
+        let state = &mut ctx.accounts.state;
+        let game = &mut ctx.accounts.game;
+        let clock = Clock::get()?;
+
+        game.set_inner(Game::init(
+            state.total_games,
+            game_type,
+            ctx.accounts.player1.key(),
+            wager_amount,
+            rounds_to_win,
+            off_chain_rules,
+            ctx.accounts.mint.key(),
+            ctx.accounts.game_type_config.platform_fee_bps,
+            clock.unix_timestamp,
+        ));
+        game.set_invitation(invited_player, join_key)?;
+
+        // Transfer wager from player to the vault (AFTER accounts are initialized)
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.player1_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.player1.to_account_info(),
+                },
+            ),
+            wager_amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        // A Token-2022 transfer fee may have withheld part of the wager; the
+        // pot is what actually reached the vault
+        ctx.accounts.vault.reload()?;
+        game.player1_deposited = ctx.accounts.vault.amount;
+
+        // Update player state to track game
+        let player_state = &mut ctx.accounts.player_state;
+        player_state.player = ctx.accounts.player1.key();
+        player_state.add_game(state.total_games, state.max_active_games_per_player)?;
+
+        // total_volume is in lamports, so token wagers aren't added to it
+        state.total_games += 1;
+        state.active_games += 1;
+
+        emit!(GameCreated {
+            game_id: game.game_id,
+            player1: game.player1,
+            wager_amount,
+            game_type,
+            rounds_to_win,
+            off_chain_rules,
+            mint: game.mint,
+            invited_player,
         });
 
         Ok(())
     }
 
     pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_JOIN)?;
         let game = &mut ctx.accounts.game;
         let clock = Clock::get()?;
 
-        require!(game.status == 0, ErrorCode::GameNotWaiting);
+        require!(game.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
         require!(game.player2 == Pubkey::default(), ErrorCode::GameAlreadyJoined);
         require!(game.player1 != ctx.accounts.player2.key(), ErrorCode::CannotJoinOwnGame);
+        let join_signer = ctx.accounts.join_signer.as_ref().map(|signer| signer.key());
+        game.require_can_join(ctx.accounts.player2.key(), join_signer)?;
+        // A spectator who bet on the game can't then play in it
+        require!(ctx.accounts.bet.data_is_empty(), ErrorCode::PlayerCannotBet);
 
         game.player2 = ctx.accounts.player2.key();
-        game.status = 1; // Active
+        game.status = GameStatus::Active;
         game.player2_deposited = game.wager_amount;
-        game.last_move_time = clock.unix_timestamp;
+        game.last_move_time = ctx.accounts.state.play_time(clock.unix_timestamp);
 
         // Update player2 state to track game (BEFORE transfer to ensure account exists)
+        let state = &mut ctx.accounts.state;
         let player2_state = &mut ctx.accounts.player2_state;
         player2_state.player = ctx.accounts.player2.key();
-        player2_state.current_game_id = Some(game.game_id);
+        player2_state.add_game(game.game_id, state.max_active_games_per_player)?;
 
-        let state = &mut ctx.accounts.state;
-        state.total_volume += game.wager_amount;
+        // Transfer the wager from player2 to escrow (AFTER accounts are initialized)
+        match ctx.accounts.token_escrow.for_game(game, state.treasury)? {
+            Some(tokens) => {
+                game.player2_deposited = tokens.deposit(
+                    Payee::Player2,
+                    &ctx.accounts.player2.to_account_info(),
+                    game.wager_amount,
+                )?;
+            }
+            None => {
+                // Volume is tracked in lamports, so only SOL games count
+                state.total_volume += game.wager_amount;
 
-        // Transfer SOL from player2 to escrow (AFTER accounts are initialized)
-        let ix = system_instruction::transfer(
-            &ctx.accounts.player2.key(),
-            &ctx.accounts.escrow.key(),
-            game.wager_amount,
-        );
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.player2.to_account_info(),
-                ctx.accounts.escrow.to_account_info(),
-            ],
-        )?;
+                let ix = system_instruction::transfer(
+                    &ctx.accounts.player2.key(),
+                    &ctx.accounts.escrow.key(),
+                    game.wager_amount,
+                );
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[
+                        ctx.accounts.player2.to_account_info(),
+                        ctx.accounts.escrow.to_account_info(),
+                    ],
+                )?;
+            }
+        }
 
         emit!(GameJoined {
             game_id: game.game_id,
@@ -185,37 +760,23 @@ pub mod korus_game_escrow {
         let game = &mut ctx.accounts.game;
 
         // Only player1 can cancel and only if waiting for player2
-        require!(game.status == 0, ErrorCode::GameNotWaiting);
+        require!(game.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
         require!(game.player1 == ctx.accounts.player1.key(), ErrorCode::NotGameCreator);
 
-        game.status = 3; // Cancelled
+        game.status = GameStatus::Cancelled;
 
-        // Refund player1
-        let game_key = game.key();
-        let seeds = &[
-            b"escrow",
-            game_key.as_ref(),
-            &[ctx.bumps.escrow],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let refund_ix = system_instruction::transfer(
-            &ctx.accounts.escrow.key(),
-            &ctx.accounts.player1.key(),
-            game.player1_deposited,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &refund_ix,
-            &[
-                ctx.accounts.escrow.to_account_info(),
-                ctx.accounts.player1.to_account_info(),
-            ],
-            signer_seeds,
+        // Refund player1 the whole escrow (their deposit plus any overfunding)
+        let tokens = ctx.accounts.token_escrow.for_game(game, ctx.accounts.state.treasury)?;
+        let refund_amount = refund_creator(
+            game,
+            &ctx.accounts.escrow,
+            ctx.bumps.escrow,
+            &ctx.accounts.player1.to_account_info(),
+            tokens,
         )?;
 
         // Clear player state game tracking
-        let player_state = &mut ctx.accounts.player_state;
-        player_state.current_game_id = None;
+        ctx.accounts.player_state.remove_game(game.game_id);
 
         // Update global state
         let state = &mut ctx.accounts.state;
@@ -223,7 +784,7 @@ pub mod korus_game_escrow {
 
         emit!(GameCancelled {
             game_id: game.game_id,
-            refund_amount: game.player1_deposited,
+            refund_amount,
         });
 
         Ok(())
@@ -239,54 +800,49 @@ pub mod korus_game_escrow {
             ErrorCode::UnauthorizedCaller
         );
 
-        // Only cancel games that are waiting for player2
-        require!(game.status == 0, ErrorCode::GameNotWaiting);
-
-        game.status = 3; // Cancelled
+        // Only cancel games that have waited too long for player2
+        require!(game.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
+        require!(
+            Clock::get()?.unix_timestamp - game.created_at
+                >= ctx.accounts.game_type_config.waiting_expiry_seconds,
+            ErrorCode::GameNotExpired
+        );
 
-        // Refund player1
-        let game_key = game.key();
-        let seeds = &[
-            b"escrow",
-            game_key.as_ref(),
-            &[ctx.bumps.escrow],
-        ];
-        let signer_seeds = &[&seeds[..]];
+        game.status = GameStatus::Cancelled;
 
-        let refund_ix = system_instruction::transfer(
-            &ctx.accounts.escrow.key(),
-            &ctx.accounts.player1.key(),
-            game.player1_deposited,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &refund_ix,
-            &[
-                ctx.accounts.escrow.to_account_info(),
-                ctx.accounts.player1.to_account_info(),
-            ],
-            signer_seeds,
+        // Refund player1 the whole escrow (their deposit plus any overfunding)
+        let tokens = ctx.accounts.token_escrow.for_game(game, state.treasury)?;
+        let refund_amount = refund_creator(
+            game,
+            &ctx.accounts.escrow,
+            ctx.bumps.escrow,
+            &ctx.accounts.player1,
+            tokens,
         )?;
 
         // Clear player state game tracking
-        let player_state = &mut ctx.accounts.player_state;
-        player_state.current_game_id = None;
+        ctx.accounts.player_state.remove_game(game.game_id);
 
         // Update global state
         state.active_games = state.active_games.saturating_sub(1);
 
         emit!(GameCancelled {
             game_id: game.game_id,
-            refund_amount: game.player1_deposited,
+            refund_amount,
         });
 
         Ok(())
     }
 
     pub fn complete_game(ctx: Context<CompleteGame>, winner: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
         let game = &mut ctx.accounts.game;
         let state = &mut ctx.accounts.state;
 
-        require!(game.status == 1, ErrorCode::GameNotActive);
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+
+        // Games with on-chain rules are decided by their own instructions, not the backend
+        require!(game.off_chain_rules, ErrorCode::OutcomeDecidedOnChain);
 
         // If winner is provided, verify it's one of the players
         if let Some(winner_pubkey) = winner {
@@ -306,217 +862,311 @@ pub mod korus_game_escrow {
         // Prevent double completion
         require!(game.winner == Pubkey::default(), ErrorCode::GameAlreadyCompleted);
 
-        game.status = 2; // Completed
-        game.winner = winner.unwrap_or(Pubkey::default()); // Default means draw
+        // Large wagers are settled by the oracle set instead
+        require!(
+            game.wager_amount < state.oracle_min_wager,
+            ErrorCode::OracleSettlementRequired
+        );
 
-        // Calculate amounts
-        let total_pot = game.player1_deposited + game.player2_deposited;
-        let platform_fee = (total_pot * state.platform_fee_bps as u64) / 10000;
+        // Verdicts must be disputable once a dispute window is configured
+        require!(
+            state.dispute_window_seconds == 0,
+            ErrorCode::DisputeWindowActive
+        );
 
-        // Handle draw vs winner payouts
-        let (payout_player1, payout_player2) = if let Some(winner_pubkey) = winner {
-            // Winner takes all (minus platform fee)
-            let winner_amount = total_pot - platform_fee;
-            if winner_pubkey == game.player1 {
-                (winner_amount, 0u64)
-            } else {
-                (0u64, winner_amount)
-            }
-        } else {
-            // Draw: split pot equally (minus platform fee)
-            let remaining = total_pot - platform_fee;
-            let half = remaining / 2;
-            (half, remaining - half) // Handle odd amounts
-        };
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
 
-        // PDA signer seeds for escrow
-        let game_key = game.key();
-        let seeds = &[
-            b"escrow",
-            game_key.as_ref(),
-            &[ctx.bumps.escrow],
-        ];
-        let signer_seeds = &[&seeds[..]];
+    // First phase of an authority verdict on an off-chain game: records the
+    // result and opens the dispute window. Nothing is paid out until
+    // finalize_result.
+    pub fn propose_result(ctx: Context<ProposeResult>, winner: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+        let game = &mut ctx.accounts.game;
+        let state = &ctx.accounts.state;
 
-        // Transfer platform fee to treasury
-        let fee_ix = system_instruction::transfer(
-            &ctx.accounts.escrow.key(),
-            &state.treasury,
-            platform_fee,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &fee_ix,
-            &[
-                ctx.accounts.escrow.to_account_info(),
-                ctx.accounts.treasury.to_account_info(),
-            ],
-            signer_seeds,
-        )?;
-
-        // Transfer payouts to players
-        if payout_player1 > 0 {
-            let player1_ix = system_instruction::transfer(
-                &ctx.accounts.escrow.key(),
-                &ctx.accounts.player1.key(),
-                payout_player1,
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        require!(game.off_chain_rules, ErrorCode::OutcomeDecidedOnChain);
+        if let Some(winner_pubkey) = winner {
+            require!(
+                winner_pubkey == game.player1 || winner_pubkey == game.player2,
+                ErrorCode::InvalidWinner
             );
-            anchor_lang::solana_program::program::invoke_signed(
-                &player1_ix,
-                &[
-                    ctx.accounts.escrow.to_account_info(),
-                    ctx.accounts.player1.to_account_info(),
-                ],
-                signer_seeds,
-            )?;
         }
+        require!(
+            game.wager_amount < state.oracle_min_wager,
+            ErrorCode::OracleSettlementRequired
+        );
 
-        if payout_player2 > 0 {
-            let player2_ix = system_instruction::transfer(
-                &ctx.accounts.escrow.key(),
-                &ctx.accounts.player2.key(),
-                payout_player2,
-            );
-            anchor_lang::solana_program::program::invoke_signed(
-                &player2_ix,
-                &[
-                    ctx.accounts.escrow.to_account_info(),
-                    ctx.accounts.player2.to_account_info(),
-                ],
-                signer_seeds,
-            )?;
-        }
+        game.status = GameStatus::ResultProposed;
+        game.proposed_winner = winner;
+        game.result_deadline = Clock::get()?.unix_timestamp + state.dispute_window_seconds;
 
-        // Clear player states game tracking
-        ctx.accounts.player1_state.current_game_id = None;
-        ctx.accounts.player2_state.current_game_id = None;
+        emit!(ResultProposed {
+            game_id: game.game_id,
+            winner,
+            deadline: game.result_deadline,
+        });
 
-        // Update global state
-        state.active_games = state.active_games.saturating_sub(1);
+        Ok(())
+    }
+
+    // Either player can freeze a proposed result until the arbitrator rules.
+    // Not pausable, so a bad verdict can always be stopped.
+    pub fn dispute_result(ctx: Context<DisputeResult>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
 
-        emit!(GameCompleted {
+        require!(
+            game.status == GameStatus::ResultProposed,
+            ErrorCode::NoProposedResult
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= game.result_deadline,
+            ErrorCode::DisputeWindowClosed
+        );
+
+        game.status = GameStatus::Disputed;
+
+        emit!(ResultDisputed {
             game_id: game.game_id,
-            winner,
-            player1_payout: payout_player1,
-            player2_payout: payout_player2,
-            platform_fee,
-            is_draw: winner.is_none(),
+            disputed_by: ctx.accounts.player.key(),
         });
 
         Ok(())
     }
 
-    pub fn claim_timeout_win(ctx: Context<ClaimTimeoutWin>) -> Result<()> {
+    pub fn resolve_dispute(ctx: Context<SettleResult>, winner: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
         let game = &mut ctx.accounts.game;
         let state = &mut ctx.accounts.state;
-        let clock = Clock::get()?;
 
-        require!(game.status == 1, ErrorCode::GameNotActive);
+        require!(
+            ctx.accounts.caller.key() == state.arbitrator,
+            ErrorCode::NotArbitrator
+        );
+        require!(game.status == GameStatus::Disputed, ErrorCode::GameNotDisputed);
+        if let Some(winner_pubkey) = winner {
+            require!(
+                winner_pubkey == game.player1 || winner_pubkey == game.player2,
+                ErrorCode::InvalidWinner
+            );
+        }
+
+        emit!(DisputeResolved {
+            game_id: game.game_id,
+            proposed_winner: game.proposed_winner,
+            winner,
+        });
+
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
+
+    // Pays out an undisputed proposed result; anyone can call it once the
+    // dispute window has passed
+    pub fn finalize_result(ctx: Context<SettleResult>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+        let game = &mut ctx.accounts.game;
 
-        // Check if timeout has occurred (10 minutes since last move)
-        let time_since_last_move = clock.unix_timestamp - game.last_move_time;
         require!(
-            time_since_last_move > MOVE_TIMEOUT_SECONDS,
-            ErrorCode::TimeoutNotReached
+            game.status == GameStatus::ResultProposed,
+            ErrorCode::NoProposedResult
+        );
+        require!(
+            Clock::get()?.unix_timestamp > game.result_deadline,
+            ErrorCode::DisputeWindowOpen
         );
 
-        // Claimer must be one of the players
-        let claimer = ctx.accounts.claimer.key();
+        let winner = game.proposed_winner;
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
+
+    // Settles on a result both players signed, without the backend authority.
+    // The transaction must carry Ed25519Program instructions verifying each
+    // player's signature over signatures::result_message(CONSENT_DOMAIN, ..).
+    // Agreement also overrides a proposed or disputed authority verdict.
+    pub fn settle_by_consent(ctx: Context<SignedSettlement>, winner: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+        let game = &mut ctx.accounts.game;
+
         require!(
-            claimer == game.player1 || claimer == game.player2,
-            ErrorCode::NotAPlayer
+            matches!(
+                game.status,
+                GameStatus::Active | GameStatus::ResultProposed | GameStatus::Disputed
+            ),
+            ErrorCode::GameNotActive
+        );
+        if let Some(winner_pubkey) = winner {
+            require!(
+                winner_pubkey == game.player1 || winner_pubkey == game.player2,
+                ErrorCode::InvalidWinner
+            );
+        }
+
+        let message = signatures::result_message(CONSENT_DOMAIN, game.game_id, winner);
+        let signers = signatures::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            signers.contains(&game.player1) && signers.contains(&game.player2),
+            ErrorCode::MissingPlayerSignature
         );
 
-        // Winner is the player who is NOT supposed to make the current move
-        // (The player whose turn it is has timed out)
-        let winner = if game.current_turn == game.player1 {
-            game.player2
-        } else {
-            game.player1
-        };
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
 
-        // Only the non-timed-out player can claim
-        require!(claimer == winner, ErrorCode::CannotClaimOwnTimeout);
-
-        game.status = 2; // Completed
-        game.winner = winner;
-
-        // Calculate amounts
-        let total_pot = game.player1_deposited + game.player2_deposited;
-        let platform_fee = (total_pot * state.platform_fee_bps as u64) / 10000;
-        let winner_amount = total_pot - platform_fee;
-
-        // PDA signer seeds for escrow
-        let game_key = game.key();
-        let seeds = &[
-            b"escrow",
-            game_key.as_ref(),
-            &[ctx.bumps.escrow],
-        ];
-        let signer_seeds = &[&seeds[..]];
+    // Settles on a voucher the backend signed with State.result_signer over
+    // signatures::result_message(VOUCHER_DOMAIN, ..) followed by the expiry
+    // (i64 LE). Stands in for complete_game, so the same limits apply, but
+    // anyone holding the voucher can submit it.
+    pub fn settle_with_voucher(
+        ctx: Context<SignedSettlement>,
+        winner: Option<Pubkey>,
+        expiry: i64,
+    ) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+        let game = &mut ctx.accounts.game;
+        let state = &mut ctx.accounts.state;
 
-        // Transfer platform fee to treasury
-        let fee_ix = system_instruction::transfer(
-            &ctx.accounts.escrow.key(),
-            &state.treasury,
-            platform_fee,
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        require!(game.off_chain_rules, ErrorCode::OutcomeDecidedOnChain);
+        if let Some(winner_pubkey) = winner {
+            require!(
+                winner_pubkey == game.player1 || winner_pubkey == game.player2,
+                ErrorCode::InvalidWinner
+            );
+        }
+        require!(
+            game.wager_amount < state.oracle_min_wager,
+            ErrorCode::OracleSettlementRequired
+        );
+        require!(
+            state.dispute_window_seconds == 0,
+            ErrorCode::DisputeWindowActive
         );
-        anchor_lang::solana_program::program::invoke_signed(
-            &fee_ix,
-            &[
-                ctx.accounts.escrow.to_account_info(),
-                ctx.accounts.treasury.to_account_info(),
-            ],
-            signer_seeds,
-        )?;
 
-        // Transfer winnings to winner (timeout always has a winner, no draw)
-        let winner_account = if winner == game.player1 {
-            ctx.accounts.player1.key()
-        } else {
-            ctx.accounts.player2.key()
-        };
+        require!(
+            state.result_signer != Pubkey::default(),
+            ErrorCode::NoResultSigner
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expiry,
+            ErrorCode::VoucherExpired
+        );
+        let mut message = signatures::result_message(VOUCHER_DOMAIN, game.game_id, winner);
+        message.extend_from_slice(&expiry.to_le_bytes());
+        let signers = signatures::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            signers.contains(&state.result_signer),
+            ErrorCode::InvalidVoucher
+        );
 
-        let winner_ix = system_instruction::transfer(
-            &ctx.accounts.escrow.key(),
-            &winner_account,
-            winner_amount,
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
+
+    // Records one oracle's vote on a game's result and settles the game once
+    // the oracle set's threshold of current oracles agree on the same result
+    pub fn submit_result(ctx: Context<SubmitResult>, winner: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+        let game = &mut ctx.accounts.game;
+        let oracle_set = &ctx.accounts.oracle_set;
+        let oracle = ctx.accounts.oracle.key();
+
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        require!(game.off_chain_rules, ErrorCode::OutcomeDecidedOnChain);
+        // Smaller wagers are settled by the authority
+        require!(
+            game.wager_amount >= ctx.accounts.state.oracle_min_wager,
+            ErrorCode::BelowOracleMinWager
         );
-        anchor_lang::solana_program::program::invoke_signed(
-            &winner_ix,
-            &[
-                ctx.accounts.escrow.to_account_info(),
-                if winner == game.player1 {
-                    ctx.accounts.player1.to_account_info()
-                } else {
-                    ctx.accounts.player2.to_account_info()
-                },
-            ],
-            signer_seeds,
-        )?;
+        if let Some(winner_pubkey) = winner {
+            require!(
+                winner_pubkey == game.player1 || winner_pubkey == game.player2,
+                ErrorCode::InvalidWinner
+            );
+        }
+        require!(oracle_set.oracles.contains(&oracle), ErrorCode::NotAnOracle);
 
-        // Clear player states game tracking
-        ctx.accounts.player1_state.current_game_id = None;
-        ctx.accounts.player2_state.current_game_id = None;
+        let record = &mut ctx.accounts.attestations;
+        record.game_id = game.game_id;
+        require!(
+            !record.attestations.iter().any(|vote| vote.oracle == oracle),
+            ErrorCode::AlreadyAttested
+        );
+        require!(
+            record.attestations.len() < MAX_ORACLES,
+            ErrorCode::TooManyAttestations
+        );
+        record.attestations.push(Attestation { oracle, winner });
 
-        // Update global state
-        state.active_games = state.active_games.saturating_sub(1);
+        // Votes from oracles removed since they voted no longer count
+        let votes = record
+            .attestations
+            .iter()
+            .filter(|vote| vote.winner == winner && oracle_set.oracles.contains(&vote.oracle))
+            .count() as u8;
 
-        emit!(GameTimeout {
+        emit!(ResultAttested {
             game_id: game.game_id,
+            oracle,
             winner,
-            winner_amount,
-            platform_fee,
+            votes,
+            threshold: oracle_set.threshold,
         });
 
-        Ok(())
+        if votes < oracle_set.threshold {
+            return Ok(());
+        }
+
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Result)
+    }
+
+    // Not pausable: move clocks stop while play is paused, so only time that
+    // ran out before the pause can be claimed
+    pub fn claim_timeout_win(ctx: Context<ClaimTimeoutWin>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let clock = Clock::get()?;
+
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+
+        // Claimer must be one of the players
+        let claimer = ctx.accounts.claimer.key();
+        require!(
+            claimer == game.player1 || claimer == game.player2,
+            ErrorCode::NotAPlayer
+        );
+
+        // By default the player whose turn it is has timed out (move_timeout_seconds
+        // since last move); simultaneous games decide who stalled through their rules
+        let winner = game
+            .timeout_winner(
+                ctx.accounts.state.play_time(clock.unix_timestamp),
+                ctx.accounts.game_type_config.move_timeout_seconds,
+            )?
+            .map(|seat| game.player_at(seat));
+
+        // Only the non-timed-out player can claim
+        if let Some(winner) = winner {
+            require!(claimer == winner, ErrorCode::CannotClaimOwnTimeout);
+        }
+
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, winner, FinishReason::Timeout)
     }
 
     pub fn update_move_time(ctx: Context<UpdateMoveTime>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
         let game = &mut ctx.accounts.game;
         let clock = Clock::get()?;
 
-        require!(game.status == 1, ErrorCode::GameNotActive);
-        
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+
+        // Games with on-chain rules only advance through their own instructions
+        require!(game.off_chain_rules, ErrorCode::OutcomeDecidedOnChain);
+
         // Only the current player can update (after making their move)
         require!(
             ctx.accounts.player.key() == game.current_turn,
@@ -524,7 +1174,8 @@ pub mod korus_game_escrow {
         );
 
         // Update last move time and switch turns
-        game.last_move_time = clock.unix_timestamp;
+        game.last_move_time = ctx.accounts.state.play_time(clock.unix_timestamp);
+        game.clear_offers();
         game.current_turn = if game.current_turn == game.player1 {
             game.player2
         } else {
@@ -533,90 +1184,2009 @@ pub mod korus_game_escrow {
 
         Ok(())
     }
-}
 
-// Account structures
-#[account]
-pub struct State {
-    pub authority: Pubkey,
-    pub treasury: Pubkey,
-    pub total_games: u64,
-    pub total_volume: u64,
-    pub platform_fee_bps: u16,
-    pub active_games: u64,
-}
+    pub fn make_move(ctx: Context<MakeMove>, cell: u8) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let player = ctx.accounts.player.key();
+        let move_timeout = ctx.accounts.game_type_config.move_timeout_seconds;
+        let game = &mut ctx.accounts.game;
+        let (cell, outcome) = game.play(
+            player,
+            &Move::Cell(cell),
+            ctx.accounts.state.play_time(Clock::get()?.unix_timestamp),
+            move_timeout,
+        )?;
 
-impl State {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 2 + 8;
-}
+        emit!(MoveMade {
+            game_id: game.game_id,
+            player,
+            cell,
+        });
 
-#[account]
-pub struct Game {
-    pub game_id: u64,
-    pub game_type: u8,
-    pub player1: Pubkey,
-    pub player2: Pubkey,
-    pub wager_amount: u64,
-    pub status: u8, // 0: Waiting, 1: Active, 2: Completed, 3: Cancelled
-    pub winner: Pubkey,
-    pub player1_deposited: u64,
-    pub player2_deposited: u64,
-    pub created_at: i64,
-    pub last_move_time: i64,
-    pub current_turn: Pubkey,
-}
+        ctx.accounts.settle_outcome(ctx.bumps.escrow, outcome)
+    }
 
-impl Game {
-    pub const LEN: usize = 8 + 1 + 32 + 32 + 8 + 1 + 32 + 8 + 8 + 8 + 8 + 32;
-}
+    pub fn drop_piece(ctx: Context<MakeMove>, column: u8) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let player = ctx.accounts.player.key();
+        let move_timeout = ctx.accounts.game_type_config.move_timeout_seconds;
+        let game = &mut ctx.accounts.game;
+        let (cell, outcome) = game.play(
+            player,
+            &Move::Column(column),
+            ctx.accounts.state.play_time(Clock::get()?.unix_timestamp),
+            move_timeout,
+        )?;
 
-#[account]
-pub struct PlayerState {
-    pub player: Pubkey,
-    pub current_game_id: Option<u64>,
-    pub _padding: u8, // Keep same size as before (was has_active_game)
-}
+        emit!(MoveMade {
+            game_id: game.game_id,
+            player,
+            cell,
+        });
 
-impl PlayerState {
-    pub const LEN: usize = 32 + 9 + 1; // Pubkey + Option<u64> + padding = 42 bytes
-}
+        ctx.accounts.settle_outcome(ctx.bumps.escrow, outcome)
+    }
 
-// Contexts
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + State::LEN,
-        seeds = [b"state"],
+    pub fn commit_choice(ctx: Context<CommitChoice>, commitment: [u8; 32]) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let player = ctx.accounts.player.key();
+        let move_timeout = ctx.accounts.game_type_config.move_timeout_seconds;
+        let game = &mut ctx.accounts.game;
+        // Committing never ends a round, so no settlement accounts are needed
+        game.play(
+            player,
+            &Move::Commit(commitment),
+            ctx.accounts.state.play_time(Clock::get()?.unix_timestamp),
+            move_timeout,
+        )?;
+
+        emit!(ChoiceCommitted {
+            game_id: game.game_id,
+            player,
+        });
+
+        Ok(())
+    }
+
+    pub fn reveal_choice(ctx: Context<MakeMove>, choice: u8, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let player = ctx.accounts.player.key();
+        let move_timeout = ctx.accounts.game_type_config.move_timeout_seconds;
+        let game = &mut ctx.accounts.game;
+        let (_, outcome) = game.play(
+            player,
+            &Move::Reveal { choice, salt },
+            ctx.accounts.state.play_time(Clock::get()?.unix_timestamp),
+            move_timeout,
+        )?;
+
+        emit!(ChoiceRevealed {
+            game_id: game.game_id,
+            player,
+            choice,
+        });
+
+        ctx.accounts.settle_outcome(ctx.bumps.escrow, outcome)
+    }
+
+    // Concedes the game: the opponent takes the pot (minus the fee) at once
+    pub fn resign(ctx: Context<MakeMove>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let resigned_by = ctx.accounts.player.key();
+        let game = &mut ctx.accounts.game;
+
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+
+        let winner = game.player_at(games::opponent(game.seat_of(resigned_by)?));
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, Some(winner), FinishReason::Resigned(resigned_by))
+    }
+
+    pub fn offer_draw(ctx: Context<GameOffer>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let game = &mut ctx.accounts.game;
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+
+        game.draw_offered_by = ctx.accounts.player.key();
+
+        emit!(DrawOffered {
+            game_id: game.game_id,
+            offered_by: game.draw_offered_by,
+        });
+
+        Ok(())
+    }
+
+    // Ends the game as a draw: the pot is split after the platform fee
+    pub fn accept_draw(ctx: Context<MakeMove>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let game = &mut ctx.accounts.game;
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        game.require_offer_from_opponent(game.draw_offered_by, ctx.accounts.player.key())?;
+
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, None, FinishReason::Result)
+    }
+
+    pub fn offer_abort(ctx: Context<GameOffer>) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_PLAY)?;
+        let game = &mut ctx.accounts.game;
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        game.require_no_moves()?;
+
+        game.abort_offered_by = ctx.accounts.player.key();
+
+        emit!(AbortOffered {
+            game_id: game.game_id,
+            offered_by: game.abort_offered_by,
+        });
+
+        Ok(())
+    }
+
+    // Cancels the game and refunds both deposits in full with no platform fee.
+    // Not pausable since it only returns funds.
+    pub fn accept_abort(ctx: Context<MakeMove>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        require!(game.status == GameStatus::Active, ErrorCode::GameNotActive);
+        game.require_no_moves()?;
+        game.require_offer_from_opponent(game.abort_offered_by, ctx.accounts.player.key())?;
+
+        ctx.accounts
+            .finish_game(ctx.bumps.escrow, None, FinishReason::Aborted)
+    }
+
+    // Tournament instructions are implemented in tournament.rs
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        game_type: GameType,
+        entry_fee: u64,
+        capacity: u8,
+        rounds_to_win: u8,
+        prize_splits: Vec<u16>,
+    ) -> Result<()> {
+        tournament::create(ctx, game_type, entry_fee, capacity, rounds_to_win, prize_splits)
+    }
+
+    pub fn register_for_tournament(ctx: Context<RegisterForTournament>) -> Result<()> {
+        tournament::register(ctx)
+    }
+
+    pub fn start_tournament(ctx: Context<StartTournament>) -> Result<()> {
+        tournament::start(ctx)
+    }
+
+    pub fn create_tournament_match(
+        ctx: Context<CreateTournamentMatch>,
+        match_index: u8,
+    ) -> Result<()> {
+        tournament::create_match(ctx, match_index)
+    }
+
+    pub fn record_tournament_match(
+        ctx: Context<RecordTournamentMatch>,
+        match_index: u8,
+    ) -> Result<()> {
+        tournament::record_match(ctx, match_index)
+    }
+
+    pub fn cancel_tournament(ctx: Context<CancelTournament>) -> Result<()> {
+        tournament::cancel(ctx)
+    }
+
+    pub fn cancel_stalled_tournament(ctx: Context<CancelStalledTournament>) -> Result<()> {
+        tournament::cancel_stalled(ctx)
+    }
+
+    pub fn claim_tournament_payout(ctx: Context<ClaimTournamentPayout>) -> Result<()> {
+        tournament::claim_payout(ctx)
+    }
+
+    pub fn close_tournament(ctx: Context<CloseTournament>) -> Result<()> {
+        tournament::close(ctx)
+    }
+
+    // Opens parimutuel betting for spectators on a game until `cutoff`, or
+    // until play starts if that is sooner
+    pub fn open_bet_pool(ctx: Context<OpenBetPool>, cutoff: i64) -> Result<()> {
+        let game = &ctx.accounts.game;
+        require!(game.betting_open(), ErrorCode::BettingClosed);
+        require!(cutoff > Clock::get()?.unix_timestamp, ErrorCode::InvalidBetCutoff);
+
+        let pool = &mut ctx.accounts.bet_pool;
+        pool.game = game.key();
+        pool.cutoff = cutoff;
+        pool.fee_bps = ctx.accounts.state.bet_fee_bps;
+        pool.player1_total = 0;
+        pool.player2_total = 0;
+        pool.outcome = BetOutcome::Open;
+        pool.payout_pool = 0;
+        pool.player1_bets = 0;
+        pool.player2_bets = 0;
+
+        // Keeps the escrow rent-exempt while it holds stakes; close_bet_pool
+        // returns it to the authority
+        let reserve = Rent::get()?.minimum_balance(0);
+        let ix = system_instruction::transfer(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.bet_escrow.key(),
+            reserve,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.bet_escrow.to_account_info(),
+            ],
+        )?;
+
+        emit!(BetPoolOpened {
+            game_id: game.game_id,
+            cutoff,
+            fee_bps: pool.fee_bps,
+        });
+
+        Ok(())
+    }
+
+    // Backs `side` (1 for player1, 2 for player2). Repeat bets add to the
+    // bettor's stake on the same side; a bettor's first bet also counts
+    // toward that side's unclaimed bets, which claim_bet uses to find the
+    // last payout.
+    pub fn place_bet(ctx: Context<PlaceBet>, side: u8, amount: u64) -> Result<()> {
+        ctx.accounts.state.require_unpaused(PAUSE_BETS)?;
+        let game = &ctx.accounts.game;
+        let pool = &mut ctx.accounts.bet_pool;
+        let bettor = ctx.accounts.bettor.key();
+
+        require!(
+            game.betting_open() && Clock::get()?.unix_timestamp < pool.cutoff,
+            ErrorCode::BettingClosed
+        );
+        require!(side == 1 || side == 2, ErrorCode::InvalidBetSide);
+        require!(amount >= MIN_WAGER_FLOOR, ErrorCode::InvalidWagerAmount);
+        require!(
+            bettor != game.player1 && bettor != game.player2,
+            ErrorCode::PlayerCannotBet
+        );
+
+        let bet = &mut ctx.accounts.bet;
+        if bet.amount == 0 {
+            bet.pool = pool.key();
+            bet.bettor = bettor;
+            bet.side = side;
+            if side == 1 {
+                pool.player1_bets += 1;
+            } else {
+                pool.player2_bets += 1;
+            }
+        }
+        require!(bet.side == side, ErrorCode::BetSideMismatch);
+        bet.amount += amount;
+        if side == 1 {
+            pool.player1_total += amount;
+        } else {
+            pool.player2_total += amount;
+        }
+
+        let ix = system_instruction::transfer(&bettor, &ctx.accounts.bet_escrow.key(), amount);
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.bettor.to_account_info(),
+                ctx.accounts.bet_escrow.to_account_info(),
+            ],
+        )?;
+
+        emit!(BetPlaced {
+            game_id: game.game_id,
+            bettor,
+            side,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless once the game is final, however it was settled. A draw,
+    // a cancelled game or a pool with no bets on one side refunds everyone;
+    // otherwise the bet fee goes to the treasury and the winning side shares
+    // the rest. Not pausable, so bets can always be paid out or refunded.
+    pub fn settle_bet_pool(ctx: Context<SettleBetPool>) -> Result<()> {
+        let game = &ctx.accounts.game;
+        let pool = &mut ctx.accounts.bet_pool;
+
+        require!(pool.outcome == BetOutcome::Open, ErrorCode::BetPoolSettled);
+        require!(
+            game.status == GameStatus::Completed || game.status == GameStatus::Cancelled,
+            ErrorCode::GameNotFinished
+        );
+
+        let platform_fee = pool.settle(game);
+        if platform_fee > 0 {
+            let game_key = game.key();
+            let seeds = &[
+                b"bet_escrow",
+                game_key.as_ref(),
+                &[ctx.bumps.bet_escrow],
+            ];
+            transfer_from_escrow(
+                &ctx.accounts.bet_escrow,
+                &ctx.accounts.treasury,
+                platform_fee,
+                &[&seeds[..]],
+            )?;
+        }
+
+        emit!(BetPoolSettled {
+            game_id: game.game_id,
+            outcome: pool.outcome,
+            payout_pool: pool.payout_pool,
+            platform_fee,
+        });
+
+        Ok(())
+    }
+
+    // Pays a winning bet its share of the pool (or refunds it) and closes
+    // the Bet account; losing bets just close. The last bet still owed a
+    // payout also takes the rounding dust, leaving only the rent reserve.
+    pub fn claim_bet(ctx: Context<ClaimBet>) -> Result<()> {
+        let pool = &mut ctx.accounts.bet_pool;
+        let bet = &ctx.accounts.bet;
+
+        let reserve = Rent::get()?.minimum_balance(0);
+        let amount = pool.claim(bet, ctx.accounts.bet_escrow.lamports(), reserve)?;
+
+        if amount > 0 {
+            let game_key = pool.game;
+            let seeds = &[
+                b"bet_escrow",
+                game_key.as_ref(),
+                &[ctx.bumps.bet_escrow],
+            ];
+            transfer_from_escrow(
+                &ctx.accounts.bet_escrow,
+                &ctx.accounts.bettor.to_account_info(),
+                amount,
+                &[&seeds[..]],
+            )?;
+        }
+
+        emit!(BetClaimed {
+            game: pool.game,
+            bettor: bet.bettor,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Once every bet has been claimed, returns the rent reserve (and any
+    // stakes of a pool that never had a bet) plus the pool's own rent to the
+    // authority that opened it
+    pub fn close_bet_pool(ctx: Context<CloseBetPool>) -> Result<()> {
+        let pool = &ctx.accounts.bet_pool;
+        require!(pool.outcome != BetOutcome::Open, ErrorCode::BetPoolNotSettled);
+        require!(
+            pool.player1_bets + pool.player2_bets == 0,
+            ErrorCode::BetsUnclaimed
+        );
+
+        let escrow = &ctx.accounts.bet_escrow;
+        let reserve_returned = escrow.lamports();
+        if reserve_returned > 0 {
+            let game_key = pool.game;
+            let seeds = &[
+                b"bet_escrow",
+                game_key.as_ref(),
+                &[ctx.bumps.bet_escrow],
+            ];
+            transfer_from_escrow(
+                escrow,
+                &ctx.accounts.authority.to_account_info(),
+                reserve_returned,
+                &[&seeds[..]],
+            )?;
+        }
+
+        emit!(BetPoolClosed {
+            game: pool.game,
+            reserve_returned,
+        });
+
+        Ok(())
+    }
+
+    // Rewrites a Game account created before the typed layout. Waiting games
+    // pick up the on-chain rules; games already in play keep being refereed by
+    // the authority since their earlier moves were never recorded on-chain.
+    // Games of a type this program doesn't know can't be played on-chain, so
+    // they are left to the authority too rather than stuck.
+    pub fn migrate_game(ctx: Context<MigrateGame>) -> Result<()> {
+        let game_info = ctx.accounts.game.to_account_info();
+
+        let legacy = {
+            let data = game_info.try_borrow_data()?;
+            require!(data.len() == 8 + LegacyGame::LEN, ErrorCode::GameAlreadyMigrated);
+            require!(
+                data[..8] == Game::DISCRIMINATOR,
+                ErrorCode::InvalidGameAccount
+            );
+            LegacyGame::deserialize(&mut &data[8..])?
+        };
+
+        // Only genuine game PDAs can be rewritten
+        let (expected_key, _) = Pubkey::find_program_address(
+            &[b"game", legacy.game_id.to_le_bytes().as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(game_info.key(), expected_key, ErrorCode::InvalidGameAccount);
+
+        let known_type = GameType::try_from(legacy.game_type).ok();
+        let status = GameStatus::try_from(legacy.status)?;
+
+        grow_account(
+            &game_info,
+            &ctx.accounts.authority.to_account_info(),
+            8 + Game::LEN,
+        )?;
+
+        let mut game = Game::init(
+            legacy.game_id,
+            known_type.unwrap_or(GameType::TicTacToe),
+            legacy.player1,
+            legacy.wager_amount,
+            1,
+            // Moves so far were only tracked off-chain
+            status == GameStatus::Active || known_type.is_none(),
+            Pubkey::default(),
+            ctx.accounts.state.platform_fee_bps, // The legacy program's global fee
+            legacy.created_at,
+        );
+        game.player2 = legacy.player2;
+        game.status = status;
+        game.winner = legacy.winner;
+        game.player1_deposited = legacy.player1_deposited;
+        game.player2_deposited = legacy.player2_deposited;
+        game.last_move_time = legacy.last_move_time;
+        game.current_turn = legacy.current_turn;
+        game.try_serialize(&mut &mut game_info.try_borrow_mut_data()?[..])?;
+
+        emit!(GameMigrated {
+            game_id: game.game_id,
+            status: game.status,
+            off_chain_rules: game.off_chain_rules,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless cleanup of a finished game: sweeps anything left in the
+    // escrow and closes the Game account, returning the rent to player1 who
+    // paid for both in create_game. Token games also close their vault.
+    pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
+        let game = &ctx.accounts.game;
+
+        require!(
+            game.status == GameStatus::Completed || game.status == GameStatus::Cancelled,
+            ErrorCode::GameNotFinished
+        );
+        // The bracket reads the result from the match, which record_tournament_match closes
+        require!(game.tournament == Pubkey::default(), ErrorCode::TournamentMatch);
+        require_bet_pool_settled(&ctx.accounts.bet_pool)?;
+
+        let tokens = ctx.accounts.token_escrow.for_game(game, ctx.accounts.state.treasury)?;
+        let swept = refund_creator(
+            game,
+            &ctx.accounts.escrow,
+            ctx.bumps.escrow,
+            &ctx.accounts.player1,
+            tokens,
+        )?;
+        if let Some(tokens) = tokens {
+            tokens.harvest_withheld_fees()?;
+            let game_key = game.key();
+            let seeds = &[b"escrow", game_key.as_ref(), &[ctx.bumps.escrow]];
+            let accounts = CloseAccount {
+                account: tokens.vault()?.to_account_info(),
+                destination: ctx.accounts.player1.clone(),
+                authority: ctx.accounts.escrow.clone(),
+            };
+            token_interface::close_account(CpiContext::new_with_signer(
+                tokens.token_program()?,
+                accounts,
+                &[&seeds[..]],
+            ))?;
+        }
+
+        emit!(GameClosed {
+            game_id: game.game_id,
+            rent_refunded: game.to_account_info().lamports(),
+            escrow_swept: swept,
+        });
+
+        Ok(())
+    }
+
+    // Lets a player reclaim the rent of their PlayerState once they have no live game
+    pub fn close_player_state(ctx: Context<ClosePlayerState>) -> Result<()> {
+        require!(
+            !ctx.accounts.player_state.has_games(),
+            ErrorCode::PlayerAlreadyInGame
+        );
+        Ok(())
+    }
+
+    // Grows the State account created before the fields added after launch and
+    // fills those fields with their defaults
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        {
+            let data = state_info.try_borrow_data()?;
+            require!(data.len() == 8 + LEGACY_STATE_LEN, ErrorCode::StateAlreadyMigrated);
+            require!(data[..8] == State::DISCRIMINATOR, ErrorCode::InvalidStateAccount);
+            // Authority is the first field in every State layout
+            require!(
+                data[8..40] == ctx.accounts.authority.key().to_bytes(),
+                ErrorCode::UnauthorizedCaller
+            );
+        }
+
+        // New fields are appended, so the zero-filled tail decodes cleanly
+        grow_account(
+            &state_info,
+            &ctx.accounts.authority.to_account_info(),
+            8 + State::LEN,
+        )?;
+        let mut state = State::try_deserialize(&mut &state_info.try_borrow_data()?[..])?;
+        state.max_active_games_per_player = DEFAULT_MAX_ACTIVE_GAMES;
+        state.minimum_wager = DEFAULT_MINIMUM_WAGER;
+        state.maximum_wager = DEFAULT_MAXIMUM_WAGER;
+        state.move_timeout_seconds = DEFAULT_MOVE_TIMEOUT_SECONDS;
+        state.pending_authority = Pubkey::default();
+        state.paused = 0;
+        state.play_paused_at = 0;
+        state.play_paused_seconds = 0;
+        state.oracle_min_wager = u64::MAX;
+        state.arbitrator = Pubkey::default();
+        state.dispute_window_seconds = 0;
+        state.result_signer = Pubkey::default();
+        state.total_tournaments = 0;
+        state.bet_fee_bps = DEFAULT_BET_FEE_BPS;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+
+    // Converts a PlayerState from the single current_game_id layout to the
+    // active game set. Permissionless; the caller pays for the extra space.
+    pub fn migrate_player_state(ctx: Context<MigratePlayerState>) -> Result<()> {
+        let player_state_info = ctx.accounts.player_state.to_account_info();
+
+        let legacy = {
+            let data = player_state_info.try_borrow_data()?;
+            require!(
+                data.len() == 8 + LegacyPlayerState::LEN,
+                ErrorCode::PlayerStateAlreadyMigrated
+            );
+            require!(
+                data[..8] == PlayerState::DISCRIMINATOR,
+                ErrorCode::InvalidPlayerStateAccount
+            );
+            LegacyPlayerState::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(
+            legacy.player,
+            ctx.accounts.player.key(),
+            ErrorCode::InvalidPlayerStateAccount
+        );
+
+        grow_account(
+            &player_state_info,
+            &ctx.accounts.payer.to_account_info(),
+            8 + PlayerState::LEN,
+        )?;
+
+        let player_state = PlayerState {
+            player: legacy.player,
+            active_game_ids: legacy.current_game_id.into_iter().collect(),
+            match_game_ids: Vec::new(),
+        };
+        player_state.try_serialize(&mut &mut player_state_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
+
+// Bounds shared by the global config and the per-game-type configs
+fn validate_config(
+    platform_fee_bps: u16,
+    minimum_wager: u64,
+    maximum_wager: u64,
+    move_timeout_seconds: i64,
+) -> Result<()> {
+    require!(
+        platform_fee_bps <= MAX_PLATFORM_FEE_BPS,
+        ErrorCode::FeeTooHigh
+    );
+    require!(
+        minimum_wager >= MIN_WAGER_FLOOR
+            && maximum_wager <= MAX_WAGER_CEILING
+            && minimum_wager <= maximum_wager,
+        ErrorCode::InvalidWagerLimits
+    );
+    require!(
+        (MIN_MOVE_TIMEOUT_SECONDS..=MAX_MOVE_TIMEOUT_SECONDS).contains(&move_timeout_seconds),
+        ErrorCode::InvalidMoveTimeout
+    );
+    Ok(())
+}
+
+// Tops up rent from `payer` and reallocs `account` to `new_len` (zero-filling the tail)
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        let ix = system_instruction::transfer(&payer.key(), &account.key(), shortfall);
+        anchor_lang::solana_program::program::invoke(&ix, &[payer.clone(), account.clone()])?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+// Settlement helpers
+struct EscrowPayout<'a, 'info> {
+    escrow: &'a AccountInfo<'info>,
+    escrow_bump: u8,
+    treasury: &'a AccountInfo<'info>,
+    player1: &'a AccountInfo<'info>,
+    player2: &'a AccountInfo<'info>,
+    tokens: Option<&'a TokenEscrow<'info>>, // Set for token games
+}
+
+#[derive(Clone, Copy)]
+enum Payee {
+    Treasury,
+    Player1,
+    Player2,
+}
+
+impl<'a, 'info> EscrowPayout<'a, 'info> {
+    // Lamports in the escrow, or tokens in the vault for token games
+    fn balance(&self) -> Result<u64> {
+        match self.tokens {
+            Some(tokens) => Ok(tokens.vault_amount()?),
+            None => Ok(self.escrow.lamports()),
+        }
+    }
+
+    fn pay(&self, game_key: &Pubkey, payee: Payee, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let seeds = &[b"escrow", game_key.as_ref(), &[self.escrow_bump]];
+        let signer_seeds = &[&seeds[..]];
+        match self.tokens {
+            Some(tokens) => tokens.transfer(self.escrow, payee, amount, signer_seeds),
+            None => {
+                let to = match payee {
+                    Payee::Treasury => self.treasury,
+                    Payee::Player1 => self.player1,
+                    Payee::Player2 => self.player2,
+                };
+                transfer_from_escrow(self.escrow, to, amount, signer_seeds)
+            }
+        }
+    }
+}
+
+// A bet pool settles from its game's result, so the game can only be closed
+// once any pool on it has been settled
+fn require_bet_pool_settled(bet_pool: &AccountInfo) -> Result<()> {
+    if bet_pool.owner == &crate::ID {
+        let pool = BetPool::try_deserialize(&mut &bet_pool.try_borrow_data()?[..])?;
+        require!(pool.outcome != BetOutcome::Open, ErrorCode::BetPoolNotSettled);
+    }
+    Ok(())
+}
+
+struct Payout {
+    player1: u64,
+    player2: u64,
+    platform_fee: u64,
+}
+
+fn transfer_from_escrow<'info>(
+    escrow: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = system_instruction::transfer(&escrow.key(), &to.key(), amount);
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[escrow.clone(), to.clone()],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+// Marks the game completed and pays out the escrow: platform fee to the
+// treasury, the rest to the winner (or split evenly on a draw)
+fn settle_game<'info>(
+    game: &mut Account<'info, Game>,
+    state: &mut Account<'info, State>,
+    accounts: &EscrowPayout<'_, 'info>,
+    winner: Option<Pubkey>,
+) -> Result<Payout> {
+    game.status = GameStatus::Completed;
+    game.winner = winner.unwrap_or_default(); // Default means draw
+
+    // Calculate amounts
+    let total_pot = game.player1_deposited + game.player2_deposited;
+    let platform_fee = (total_pot * game.fee_bps as u64) / 10000;
+    let remaining = total_pot - platform_fee;
+
+    // Handle draw vs winner payouts
+    let (mut payout_player1, payout_player2) = match winner {
+        // Winner takes all (minus platform fee)
+        Some(winner_pubkey) if winner_pubkey == game.player1 => (remaining, 0u64),
+        Some(_) => (0u64, remaining),
+        // Draw: split pot equally (minus platform fee)
+        None => {
+            let half = remaining / 2;
+            (half, remaining - half) // Handle odd amounts
+        }
+    };
+
+    // Anything above the pot was overfunded by player1's direct deposit; return
+    // it so the escrow drains to zero
+    payout_player1 += accounts.balance()?.saturating_sub(total_pot);
+
+    require!(
+        accounts.treasury.key() == state.treasury,
+        ErrorCode::InvalidTreasury
+    );
+    let game_key = game.key();
+    accounts.pay(&game_key, Payee::Treasury, platform_fee)?;
+    accounts.pay(&game_key, Payee::Player1, payout_player1)?;
+    accounts.pay(&game_key, Payee::Player2, payout_player2)?;
+
+    // Update global state
+    state.active_games = state.active_games.saturating_sub(1);
+
+    Ok(Payout {
+        player1: payout_player1,
+        player2: payout_player2,
+        platform_fee,
+    })
+}
+
+// Returns the whole escrow (SOL, or the vault's tokens) to player1, leaving
+// it empty so no rent-paying dust is stranded
+fn refund_creator<'info>(
+    game: &Account<'info, Game>,
+    escrow: &AccountInfo<'info>,
+    escrow_bump: u8,
+    player1: &AccountInfo<'info>,
+    tokens: Option<&TokenEscrow<'info>>,
+) -> Result<u64> {
+    let game_key = game.key();
+    let seeds = &[b"escrow", game_key.as_ref(), &[escrow_bump]];
+    let signer_seeds = &[&seeds[..]];
+    let refund_amount = match tokens {
+        Some(tokens) => tokens.vault_amount()?,
+        None => escrow.lamports(),
+    };
+    if refund_amount > 0 {
+        match tokens {
+            Some(tokens) => tokens.transfer(escrow, Payee::Player1, refund_amount, signer_seeds)?,
+            None => transfer_from_escrow(escrow, player1, refund_amount, signer_seeds)?,
+        }
+    }
+    Ok(refund_amount)
+}
+
+// Marks the game cancelled and returns each player's deposit (player1 also
+// gets any overfunding) with no platform fee
+fn refund_game<'info>(
+    game: &mut Account<'info, Game>,
+    state: &mut Account<'info, State>,
+    accounts: &EscrowPayout<'_, 'info>,
+) -> Result<Payout> {
+    game.status = GameStatus::Cancelled;
+
+    let refund_player2 = game.player2_deposited;
+    let refund_player1 = accounts.balance()? - refund_player2;
+
+    let game_key = game.key();
+    accounts.pay(&game_key, Payee::Player1, refund_player1)?;
+    accounts.pay(&game_key, Payee::Player2, refund_player2)?;
+
+    state.active_games = state.active_games.saturating_sub(1);
+
+    Ok(Payout {
+        player1: refund_player1,
+        player2: refund_player2,
+        platform_fee: 0,
+    })
+}
+
+// How a game ended: decides between settling and refunding, and which event
+// is emitted
+#[derive(Clone, Copy)]
+enum FinishReason {
+    Result,           // Played out, agreed or reported
+    Timeout,          // A player ran out of time (a draw if neither acted)
+    Resigned(Pubkey), // The given player conceded
+    Aborted,          // Called off before any move: full refund, no fee
+}
+
+// The accounts a finishing game touches
+struct Settlement<'a, 'info> {
+    game: &'a mut Account<'info, Game>,
+    state: &'a mut Account<'info, State>,
+    player1_state: &'a mut Account<'info, PlayerState>,
+    player2_state: &'a mut Account<'info, PlayerState>,
+    payout: EscrowPayout<'a, 'info>,
+}
+
+impl<'a, 'info> Settlement<'a, 'info> {
+    // Pays out (or refunds) the escrow, stops tracking the game for both
+    // players and emits the event for how it ended
+    fn finish(self, winner: Option<Pubkey>, reason: FinishReason) -> Result<()> {
+        let game_id = self.game.game_id;
+        let amounts = match reason {
+            FinishReason::Aborted => refund_game(self.game, self.state, &self.payout)?,
+            _ => settle_game(self.game, self.state, &self.payout, winner)?,
+        };
+
+        // Clear player states game tracking
+        self.player1_state.remove_game(game_id);
+        self.player2_state.remove_game(game_id);
+
+        match (reason, winner) {
+            (FinishReason::Aborted, _) => emit!(GameAborted {
+                game_id,
+                player1_refund: amounts.player1,
+                player2_refund: amounts.player2,
+            }),
+            (FinishReason::Resigned(resigned_by), Some(winner)) => emit!(GameResigned {
+                game_id,
+                resigned_by,
+                winner,
+                winner_amount: amounts.player1 + amounts.player2,
+                platform_fee: amounts.platform_fee,
+            }),
+            (FinishReason::Timeout, Some(winner)) => emit!(GameTimeout {
+                game_id,
+                winner,
+                winner_amount: amounts.player1 + amounts.player2,
+                platform_fee: amounts.platform_fee,
+            }),
+            _ => emit!(GameCompleted {
+                game_id,
+                winner,
+                player1_payout: amounts.player1,
+                player2_payout: amounts.player2,
+                platform_fee: amounts.platform_fee,
+                is_draw: winner.is_none(),
+            }),
+        }
+
+        Ok(())
+    }
+}
+
+// Every instruction that can end a game names these accounts the same way,
+// so they share one finish_game
+macro_rules! impl_finish_game {
+    ($($accounts:ident),+) => {
+        $(
+            impl<'info> $accounts<'info> {
+                fn finish_game(
+                    &mut self,
+                    escrow_bump: u8,
+                    winner: Option<Pubkey>,
+                    reason: FinishReason,
+                ) -> Result<()> {
+                    let tokens = self.token_escrow.for_game(&self.game, self.treasury.key())?;
+                    Settlement {
+                        game: &mut self.game,
+                        state: &mut self.state,
+                        player1_state: &mut self.player1_state,
+                        player2_state: &mut self.player2_state,
+                        payout: EscrowPayout {
+                            escrow: &self.escrow,
+                            escrow_bump,
+                            treasury: &self.treasury,
+                            player1: &self.player1,
+                            player2: &self.player2,
+                            tokens,
+                        },
+                    }
+                    .finish(winner, reason)
+                }
+            }
+        )+
+    };
+}
+
+impl_finish_game!(
+    CompleteGame,
+    SettleResult,
+    SignedSettlement,
+    SubmitResult,
+    ClaimTimeoutWin,
+    MakeMove
+);
+
+// Account structures
+#[account]
+pub struct State {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub total_games: u64,
+    pub total_volume: u64,
+    pub platform_fee_bps: u16,
+    pub active_games: u64,
+    pub max_active_games_per_player: u8,
+    pub minimum_wager: u64,
+    pub maximum_wager: u64,
+    pub move_timeout_seconds: i64,
+    pub pending_authority: Pubkey, // Default when no transfer is in progress
+    pub paused: u8,                // PAUSE_* bits
+    pub play_paused_at: i64,       // When PAUSE_PLAY was set; 0 while play runs
+    pub play_paused_seconds: i64,  // Total time play was paused before play_paused_at
+    pub oracle_min_wager: u64,     // Wagers at or above this settle through the oracle set
+    pub arbitrator: Pubkey,        // Rules on disputed results
+    pub dispute_window_seconds: i64,
+    pub result_signer: Pubkey,     // Signs result vouchers (default when disabled)
+    pub total_tournaments: u64,
+    pub bet_fee_bps: u16,          // Fee on spectator bet pools, separate from platform_fee_bps
+}
+
+// Size of State as first deployed, before migrate_state
+const LEGACY_STATE_LEN: usize = 32 + 32 + 8 + 8 + 2 + 8;
+
+impl State {
+    pub const LEN: usize = LEGACY_STATE_LEN + 1 + 8 + 8 + 8 + 32 + 1 + 8 + 8 + 8 + 32 + 8 + 32 + 8 + 2;
+
+    fn require_unpaused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, ErrorCode::ProgramPaused);
+        Ok(())
+    }
+
+    // Unix time minus the time play has spent paused. Move clocks and reveal
+    // deadlines run on it, so a pause never uses up a player's time.
+    fn play_time(&self, now: i64) -> i64 {
+        let paused_now = if self.paused & PAUSE_PLAY != 0 {
+            now - self.play_paused_at
+        } else {
+            0
+        };
+        now - self.play_paused_seconds - paused_now
+    }
+}
+
+// Per-game-type settings, seeded from State by init_game_type_config. Games
+// keep the fee they were created with, so changing it only affects new games.
+#[account]
+pub struct GameTypeConfig {
+    pub game_type: GameType,
+    pub platform_fee_bps: u16,
+    pub minimum_wager: u64,
+    pub maximum_wager: u64,
+    pub move_timeout_seconds: i64,
+    pub waiting_expiry_seconds: i64,
+    pub enabled: bool,
+}
+
+impl GameTypeConfig {
+    pub const LEN: usize = 1 + 2 + 8 + 8 + 8 + 8 + 1;
+
+    fn wager_in_range(&self, wager_amount: u64) -> bool {
+        (self.minimum_wager..=self.maximum_wager).contains(&wager_amount)
+    }
+}
+
+#[account]
+pub struct Game {
+    pub game_id: u64,
+    pub game_type: GameType,
+    pub player1: Pubkey,
+    pub player2: Pubkey,
+    pub wager_amount: u64,
+    pub status: GameStatus,
+    pub winner: Pubkey,
+    pub player1_deposited: u64,
+    pub player2_deposited: u64,
+    pub created_at: i64,
+    pub last_move_time: i64, // On the play clock (State::play_time)
+    pub current_turn: Pubkey,
+    pub state: [u8; GAME_STATE_SIZE], // Per-round state owned by the game's GameRules
+    pub move_count: u8,
+    pub rounds_to_win: u8,
+    pub player1_rounds: u8,
+    pub player2_rounds: u8,
+    pub rounds_played: u8,
+    pub off_chain_rules: bool, // Played off-chain (or migrated mid-game): settled by a reported result
+    pub fee_bps: u16, // Platform fee locked in when the game was created
+    pub proposed_winner: Option<Pubkey>, // Result awaiting finalize_result (None is a draw)
+    pub result_deadline: i64,            // End of the dispute window
+    pub draw_offered_by: Pubkey,         // Default when no offer is open
+    pub abort_offered_by: Pubkey,        // Default when no offer is open
+    pub mint: Pubkey,                    // Wager token; default for native SOL
+    pub invited_player: Pubkey,          // Only wallet allowed to join; default for open games
+    pub join_key: Pubkey,                // Keypair derived from the join code must co-sign; default for none
+    pub tournament: Pubkey,              // Tournament this is a match of; default otherwise
+}
+
+impl Game {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 8 + 1 + 32 + 8 + 8 + 8 + 8 + 32 + GAME_STATE_SIZE + 1
+        + 1 + 1 + 1 + 1 + 1 + 2 + 33 + 8 + 32 + 32 + 32 + 32 + 32 + 32;
+
+    // A new game waiting for player2, with player1 to move first. Callers set
+    // whatever differs: invitations, tournament seating, migrated progress.
+    #[allow(clippy::too_many_arguments)]
+    fn init(
+        game_id: u64,
+        game_type: GameType,
+        player1: Pubkey,
+        wager_amount: u64,
+        rounds_to_win: u8,
+        off_chain_rules: bool,
+        mint: Pubkey,
+        fee_bps: u16,
+        now: i64,
+    ) -> Self {
+        Self {
+            game_id,
+            game_type,
+            player1,
+            player2: Pubkey::default(),
+            wager_amount,
+            status: GameStatus::Waiting,
+            winner: Pubkey::default(),
+            player1_deposited: wager_amount,
+            player2_deposited: 0,
+            created_at: now,
+            last_move_time: now,
+            current_turn: player1,
+            state: [0; GAME_STATE_SIZE],
+            move_count: 0,
+            rounds_to_win,
+            player1_rounds: 0,
+            player2_rounds: 0,
+            rounds_played: 0,
+            off_chain_rules,
+            fee_bps,
+            proposed_winner: None,
+            result_deadline: 0,
+            draw_offered_by: Pubkey::default(),
+            abort_offered_by: Pubkey::default(),
+            mint,
+            invited_player: Pubkey::default(),
+            join_key: Pubkey::default(),
+            tournament: Pubkey::default(),
+        }
+    }
+
+    fn seat_of(&self, player: Pubkey) -> Result<Seat> {
+        if player == self.player1 {
+            Ok(1)
+        } else if player == self.player2 {
+            Ok(2)
+        } else {
+            err!(ErrorCode::NotAPlayer)
+        }
+    }
+
+    fn player_at(&self, seat: Seat) -> Pubkey {
+        if seat == 1 {
+            self.player1
+        } else {
+            self.player2
+        }
+    }
+
+    // Applies a move through the game's rules, passing the turn in turn-based
+    // games. Returns the state cell the move wrote and the round outcome.
+    fn play(
+        &mut self,
+        player: Pubkey,
+        mv: &Move,
+        now: i64,
+        move_timeout: i64,
+    ) -> Result<(u8, Outcome)> {
+        require!(self.status == GameStatus::Active, ErrorCode::GameNotActive);
+        require!(!self.off_chain_rules, ErrorCode::OffChainRules);
+        let rules = self.game_type.rules();
+        if rules.is_turn_based() {
+            require!(player == self.current_turn, ErrorCode::NotYourTurn);
+        }
+
+        let ctx = MoveContext {
+            seat: self.seat_of(player)?,
+            player,
+            now,
+            move_timeout,
+        };
+        let cell = rules.apply_move(&mut self.state, &ctx, mv)?;
+
+        self.move_count += 1;
+        self.last_move_time = now;
+        self.clear_offers();
+        if rules.is_turn_based() {
+            self.current_turn = self.player_at(games::opponent(ctx.seat));
+        }
+
+        Ok((cell, rules.outcome(&self.state)))
+    }
+
+    // Draw and abort offers only stand until the next move
+    fn clear_offers(&mut self) {
+        self.draw_offered_by = Pubkey::default();
+        self.abort_offered_by = Pubkey::default();
+    }
+
+    // Restricts the second seat to `invited_player` and/or whoever knows the
+    // join code `join_key` was derived from
+    fn set_invitation(
+        &mut self,
+        invited_player: Option<Pubkey>,
+        join_key: Option<Pubkey>,
+    ) -> Result<()> {
+        self.invited_player = invited_player.unwrap_or_default();
+        self.join_key = join_key.unwrap_or_default();
+        require!(self.invited_player != self.player1, ErrorCode::CannotJoinOwnGame);
+        Ok(())
+    }
+
+    // The join code never goes on-chain: clients seed an ed25519 keypair with
+    // sha256(join code) and have it co-sign the join. A signature can't be
+    // lifted from a pending transaction the way a plaintext code could.
+    fn require_can_join(&self, player: Pubkey, join_signer: Option<Pubkey>) -> Result<()> {
+        if self.invited_player != Pubkey::default() {
+            require!(player == self.invited_player, ErrorCode::NotInvitedPlayer);
+        }
+        if self.join_key != Pubkey::default() {
+            require!(join_signer == Some(self.join_key), ErrorCode::InvalidJoinCode);
+        }
+        Ok(())
+    }
+
+    // Accepting requires an open offer from the other player
+    fn require_offer_from_opponent(&self, offered_by: Pubkey, player: Pubkey) -> Result<()> {
+        require!(
+            offered_by != Pubkey::default() && offered_by != player,
+            ErrorCode::NoOpenOffer
+        );
+        Ok(())
+    }
+
+    // Aborting is only allowed before anyone has moved; moves of migrated
+    // games were never recorded on-chain, so those can't be aborted
+    fn require_no_moves(&self) -> Result<()> {
+        require!(
+            !self.off_chain_rules && self.move_count == 0 && self.rounds_played == 0,
+            ErrorCode::GameAlreadyStarted
+        );
+        Ok(())
+    }
+
+    // The board is public, so bets close at the first move. Off-chain games
+    // never record their moves, so theirs close when player2 joins.
+    fn betting_open(&self) -> bool {
+        match self.status {
+            GameStatus::Waiting => true,
+            GameStatus::Active => {
+                !self.off_chain_rules && self.move_count == 0 && self.rounds_played == 0
+            }
+            _ => false,
+        }
+    }
+
+    fn timeout_winner(&self, now: i64, move_timeout: i64) -> Result<Option<Seat>> {
+        let clock = TurnClock {
+            to_move: self.seat_of(self.current_turn)?,
+            last_move_time: self.last_move_time,
+            now,
+            move_timeout,
+        };
+        if self.off_chain_rules {
+            // Moves of off-chain games only ever advance the turn clock
+            games::turn_timeout_winner(&clock)
+        } else {
+            self.game_type.rules().timeout_winner(&self.state, &clock)
+        }
+    }
+
+    // Scores a finished round and returns the outcome of the whole series.
+    // Drawn rounds score nothing and are replayed, except that a single-round
+    // game ends as a draw unless its rules always replay draws.
+    fn record_round(&mut self, round: &Outcome) -> Outcome {
+        self.rounds_played += 1;
+        match round {
+            Outcome::Won(1) => self.player1_rounds += 1,
+            Outcome::Won(_) => self.player2_rounds += 1,
+            _ => {}
+        }
+
+        let replays_draws = self.game_type.rules().replays_draws();
+        if self.player1_rounds >= self.rounds_to_win {
+            Outcome::Won(1)
+        } else if self.player2_rounds >= self.rounds_to_win {
+            Outcome::Won(2)
+        } else if matches!(round, Outcome::Draw) && self.rounds_to_win == 1 && !replays_draws {
+            Outcome::Draw
+        } else {
+            Outcome::InProgress
+        }
+    }
+
+    // Clears the per-round state; the players alternate who moves first
+    fn start_next_round(&mut self, now: i64) {
+        self.game_type.rules().init_state(&mut self.state);
+        self.move_count = 0;
+        self.last_move_time = now;
+        self.current_turn = if self.rounds_played % 2 == 1 {
+            self.player2
+        } else {
+            self.player1
+        };
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Waiting,
+    Active,
+    Completed,
+    Cancelled,
+    ResultProposed, // Verdict recorded, payout waits for the dispute window
+    Disputed,       // Escrow frozen until the arbitrator rules
+}
+
+impl TryFrom<u8> for GameStatus {
+    type Error = Error;
+
+    fn try_from(status: u8) -> Result<Self> {
+        // Legacy accounts predate the dispute statuses
+        match status {
+            0 => Ok(GameStatus::Waiting),
+            1 => Ok(GameStatus::Active),
+            2 => Ok(GameStatus::Completed),
+            3 => Ok(GameStatus::Cancelled),
+            _ => err!(ErrorCode::InvalidGameAccount),
+        }
+    }
+}
+
+// Game layout before the typed enums and rules engine (still held by mainnet
+// accounts until migrate_game rewrites them)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyGame {
+    pub game_id: u64,
+    pub game_type: u8,
+    pub player1: Pubkey,
+    pub player2: Pubkey,
+    pub wager_amount: u64,
+    pub status: u8, // 0: Waiting, 1: Active, 2: Completed, 3: Cancelled
+    pub winner: Pubkey,
+    pub player1_deposited: u64,
+    pub player2_deposited: u64,
+    pub created_at: i64,
+    pub last_move_time: i64,
+    pub current_turn: Pubkey,
+}
+
+impl LegacyGame {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 8 + 1 + 32 + 8 + 8 + 8 + 8 + 32;
+}
+
+#[account]
+pub struct PlayerState {
+    pub player: Pubkey,
+    pub active_game_ids: Vec<u64>, // At most State.max_active_games_per_player
+    pub match_game_ids: Vec<u64>,  // Live tournament matches, which don't count toward the limit
+}
+
+impl PlayerState {
+    pub const LEN: usize = 32 + 4 + 8 * MAX_ACTIVE_GAMES_CAP as usize + 4;
+
+    fn add_game(&mut self, game_id: u64, limit: u8) -> Result<()> {
+        require!(
+            self.active_game_ids.len() < limit as usize,
+            ErrorCode::PlayerAlreadyInGame
+        );
+        self.active_game_ids.push(game_id);
+        Ok(())
+    }
+
+    // Matches are created by the tournament, not the player, so they are
+    // never refused; the account grows to fit them
+    fn add_match(&mut self, game_id: u64) {
+        self.match_game_ids.push(game_id);
+    }
+
+    fn remove_game(&mut self, game_id: u64) {
+        self.active_game_ids.retain(|&id| id != game_id);
+        self.match_game_ids.retain(|&id| id != game_id);
+    }
+
+    fn has_games(&self) -> bool {
+        !self.active_game_ids.is_empty() || !self.match_game_ids.is_empty()
+    }
+
+    // Bytes the account needs for its current contents
+    fn space(&self) -> usize {
+        8 + 32 + 4 + 8 * self.active_game_ids.len() + 4 + 8 * self.match_game_ids.len()
+    }
+}
+
+// Wager bounds for games in an SPL token, in the mint's base units. A mint
+// can only be wagered once it has an enabled config.
+#[account]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub minimum_wager: u64,
+    pub maximum_wager: u64,
+    pub enabled: bool,
+}
+
+impl MintConfig {
+    pub const LEN: usize = 32 + 8 + 8 + 1;
+
+    fn wager_in_range(&self, wager_amount: u64) -> bool {
+        (self.minimum_wager..=self.maximum_wager).contains(&wager_amount)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BetOutcome {
+    Open,
+    Player1Won,
+    Player2Won,
+    Refund, // Draw, cancelled game or a one-sided pool
+}
+
+// Spectator parimutuel pool on a game. Stakes are held by the game's
+// bet_escrow PDA, apart from the players' wagers.
+#[account]
+pub struct BetPool {
+    pub game: Pubkey,
+    pub cutoff: i64,  // No bets at or after this time
+    pub fee_bps: u16, // State.bet_fee_bps when the pool opened
+    pub player1_total: u64,
+    pub player2_total: u64,
+    pub outcome: BetOutcome,
+    pub payout_pool: u64, // Shared by the winning side after the fee
+    pub player1_bets: u32, // Unclaimed Bet accounts backing player1
+    pub player2_bets: u32, // Unclaimed Bet accounts backing player2
+}
+
+impl BetPool {
+    pub const LEN: usize = 32 + 8 + 2 + 8 + 8 + 1 + 8 + 4 + 4;
+
+    // Decides the pool from its game's final result and returns the bet fee
+    fn settle(&mut self, game: &Game) -> u64 {
+        let winning_total = match game.status {
+            GameStatus::Completed if game.winner == game.player1 => Some(self.player1_total),
+            GameStatus::Completed if game.winner == game.player2 => Some(self.player2_total),
+            _ => None,
+        };
+        let total = self.player1_total + self.player2_total;
+        match winning_total {
+            Some(winning_total) if winning_total > 0 && winning_total < total => {
+                let platform_fee = (total * self.fee_bps as u64) / 10000;
+                self.payout_pool = total - platform_fee;
+                self.outcome = if game.winner == game.player1 {
+                    BetOutcome::Player1Won
+                } else {
+                    BetOutcome::Player2Won
+                };
+                platform_fee
+            }
+            _ => {
+                self.outcome = BetOutcome::Refund;
+                0
+            }
+        }
+    }
+
+    // Counts a bet as claimed and returns the amount to send it: its stake
+    // on a refund, its pro-rata share of the payout pool if it won, nothing
+    // if it lost. Shares round down, so the last bet still owed a payout
+    // takes what is left of the escrow above the rent reserve.
+    fn claim(&mut self, bet: &Bet, escrow_balance: u64, reserve: u64) -> Result<u64> {
+        let amount = match self.outcome {
+            BetOutcome::Open => return err!(ErrorCode::BetPoolNotSettled),
+            BetOutcome::Refund => bet.amount,
+            BetOutcome::Player1Won if bet.side == 1 => self.share(bet.amount, self.player1_total),
+            BetOutcome::Player2Won if bet.side == 2 => self.share(bet.amount, self.player2_total),
+            _ => 0,
+        };
+
+        if bet.side == 1 {
+            self.player1_bets -= 1;
+        } else {
+            self.player2_bets -= 1;
+        }
+        let outstanding = match self.outcome {
+            BetOutcome::Player1Won => self.player1_bets,
+            BetOutcome::Player2Won => self.player2_bets,
+            _ => self.player1_bets + self.player2_bets,
+        };
+        if amount > 0 && outstanding == 0 {
+            Ok(escrow_balance.saturating_sub(reserve))
+        } else {
+            Ok(amount)
+        }
+    }
+
+    fn share(&self, stake: u64, winning_total: u64) -> u64 {
+        (stake as u128 * self.payout_pool as u128 / winning_total as u128) as u64
+    }
+}
+
+#[account]
+pub struct Bet {
+    pub pool: Pubkey,
+    pub bettor: Pubkey,
+    pub side: u8, // 1 backs player1, 2 backs player2
+    pub amount: u64,
+}
+
+impl Bet {
+    pub const LEN: usize = 32 + 32 + 1 + 8;
+}
+
+#[account]
+pub struct OracleSet {
+    pub oracles: Vec<Pubkey>,
+    pub threshold: u8, // Matching attestations needed to settle
+}
+
+impl OracleSet {
+    pub const LEN: usize = 4 + 32 * MAX_ORACLES + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Attestation {
+    pub oracle: Pubkey,
+    pub winner: Option<Pubkey>, // None attests a draw
+}
+
+// Oracle votes on one game's result, kept after settlement as an audit trail
+#[account]
+pub struct ResultAttestations {
+    pub game_id: u64,
+    pub attestations: Vec<Attestation>,
+}
+
+impl ResultAttestations {
+    pub const LEN: usize = 8 + 4 + (32 + 33) * MAX_ORACLES;
+}
+
+// PlayerState layout before the active game set (still held by accounts
+// until migrate_player_state rewrites them)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyPlayerState {
+    pub player: Pubkey,
+    pub current_game_id: Option<u64>,
+}
+
+impl LegacyPlayerState {
+    pub const LEN: usize = 32 + 9 + 1; // Pubkey + Option<u64> + padding = 42 bytes
+}
+
+// Contexts
+
+// Token accounts of a game wagered in an SPL token (classic or Token-2022).
+// All of them are omitted for SOL games; token games need the mint, vault
+// and token program plus the accounts of whoever gets paid.
+#[derive(Accounts)]
+pub struct TokenEscrow<'info> {
+    // Passed writable to close_game when the vault holds withheld transfer fees
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player1_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player2_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> TokenEscrow<'info> {
+    // Checks the supplied token accounts belong to the game (or, for the
+    // treasury, to `treasury`). Returns None for SOL games.
+    fn for_game(&self, game: &Account<'info, Game>, treasury: Pubkey) -> Result<Option<&Self>> {
+        if game.mint == Pubkey::default() {
+            return Ok(None);
+        }
+        let (vault_key, _) =
+            Pubkey::find_program_address(&[b"vault", game.key().as_ref()], &crate::ID);
+        require_keys_eq!(self.vault()?.key(), vault_key, ErrorCode::InvalidTokenAccount);
+        require_keys_eq!(self.mint()?.key(), game.mint, ErrorCode::InvalidTokenAccount);
+        require!(self.token_program.is_some(), ErrorCode::MissingTokenAccount);
+        for (account, owner) in [
+            (&self.treasury_token_account, treasury),
+            (&self.player1_token_account, game.player1),
+            (&self.player2_token_account, game.player2),
+        ] {
+            if let Some(account) = account {
+                require!(
+                    account.mint == game.mint && account.owner == owner,
+                    ErrorCode::InvalidTokenAccount
+                );
+            }
+        }
+        Ok(Some(self))
+    }
+
+    fn mint(&self) -> Result<&InterfaceAccount<'info, Mint>> {
+        self.mint
+            .as_ref()
+            .ok_or_else(|| error!(ErrorCode::MissingTokenAccount))
+    }
+
+    fn vault(&self) -> Result<&InterfaceAccount<'info, TokenAccount>> {
+        self.vault
+            .as_ref()
+            .ok_or_else(|| error!(ErrorCode::MissingTokenAccount))
+    }
+
+    // Current vault balance, read from the account data rather than the
+    // snapshot taken when the instruction started
+    fn vault_amount(&self) -> Result<u64> {
+        let vault = self.vault()?.to_account_info();
+        let data = vault.try_borrow_data()?;
+        Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+    }
+
+    // A Token-2022 vault can't be closed while it holds transfer fees withheld
+    // from deposits. Harvesting moves them to the mint and is permissionless.
+    fn harvest_withheld_fees(&self) -> Result<()> {
+        let vault = self.vault()?.to_account_info();
+        if vault.owner != &spl_token_2022::ID {
+            return Ok(());
+        }
+        let withheld = {
+            let data = vault.try_borrow_data()?;
+            let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+            account
+                .get_extension::<TransferFeeAmount>()
+                .map_or(0, |fee| u64::from(fee.withheld_amount))
+        };
+        if withheld > 0 {
+            let mint = self.mint()?.to_account_info();
+            let ix =
+                harvest_withheld_tokens_to_mint(&spl_token_2022::ID, &mint.key(), &[&vault.key()])?;
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[mint, vault, self.token_program()?],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn account(&self, payee: Payee) -> Result<&InterfaceAccount<'info, TokenAccount>> {
+        match payee {
+            Payee::Treasury => &self.treasury_token_account,
+            Payee::Player1 => &self.player1_token_account,
+            Payee::Player2 => &self.player2_token_account,
+        }
+        .as_ref()
+        .ok_or_else(|| error!(ErrorCode::MissingTokenAccount))
+    }
+
+    fn token_program(&self) -> Result<AccountInfo<'info>> {
+        self.token_program
+            .as_ref()
+            .map(|program| program.to_account_info())
+            .ok_or_else(|| error!(ErrorCode::MissingTokenAccount))
+    }
+
+    // Pays out of the vault, signed by the escrow PDA that owns it
+    fn transfer(
+        &self,
+        escrow: &AccountInfo<'info>,
+        payee: Payee,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let accounts = TransferChecked {
+            from: self.vault()?.to_account_info(),
+            mint: self.mint()?.to_account_info(),
+            to: self.account(payee)?.to_account_info(),
+            authority: escrow.clone(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(self.token_program()?, accounts, signer_seeds),
+            amount,
+            self.mint()?.decimals,
+        )
+    }
+
+    // Moves a player's wager into the vault and returns how much arrived,
+    // which is less than `amount` for mints with a transfer fee
+    fn deposit(&self, payee: Payee, player: &AccountInfo<'info>, amount: u64) -> Result<u64> {
+        let before = self.vault_amount()?;
+        let accounts = TransferChecked {
+            from: self.account(payee)?.to_account_info(),
+            mint: self.mint()?.to_account_info(),
+            to: self.vault()?.to_account_info(),
+            authority: player.clone(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new(self.token_program()?, accounts),
+            amount,
+            self.mint()?.decimals,
+        )?;
+        Ok(self.vault_amount()? - before)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + State::LEN,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct CreateGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(seeds = [b"game_type".as_ref(), &[game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + Game::LEN,
+        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    #[account(
+        init_if_needed,
+        payer = player1,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player1.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct CreateGameWithDeposit<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(seeds = [b"game_type".as_ref(), &[game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + Game::LEN,
+        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    #[account(
+        init_if_needed,
+        payer = player1,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player1.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL (player should have already deposited)
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct CreateTokenGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(seeds = [b"game_type".as_ref(), &[game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    #[account(seeds = [b"mint_config", mint.key().as_ref()], bump)]
+    pub mint_config: Account<'info, MintConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = player1,
+        space = 8 + Game::LEN,
+        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    #[account(
+        init_if_needed,
+        payer = player1,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player1.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA; owns the vault and signs its transfers
+    #[account(
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = player1,
+        seeds = [b"vault", game.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = player1,
+        token::token_program = token_program
+    )]
+    pub player1_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        init_if_needed,
+        payer = player2,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player2.key().as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: The game's bet pool PDA, only used to derive the bet address
+    #[account(seeds = [b"bet_pool", game.key().as_ref()], bump)]
+    pub bet_pool: UncheckedAccount<'info>,
+    /// CHECK: Player 2's bet PDA on this game, which must not exist
+    #[account(seeds = [b"bet", bet_pool.key().as_ref(), player2.key().as_ref()], bump)]
+    pub bet: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub player2: Signer<'info>,
+    // Keypair derived from the join code, for games that require one
+    pub join_signer: Option<Signer<'info>>,
+    pub system_program: Program<'info, System>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        mut,
+        seeds = [b"player", player1.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub player1: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorityCancelExpiredGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(seeds = [b"game_type".as_ref(), &[game.game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Player 1 account (doesn't need to sign for authority cancellation, must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotGameCreator)]
+    pub player1: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteGame<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
+        bump
+    )]
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
+        bump
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (must match state)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeResult<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeResult<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        constraint = player.key() == game.player1 || player.key() == game.player2 @ ErrorCode::NotAPlayer
+    )]
+    pub player: Signer<'info>,
+}
+
+// finalize_result (anyone) and resolve_dispute (arbitrator only)
+#[derive(Accounts)]
+pub struct SettleResult<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
+        bump
+    )]
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
+    #[account(
+        mut,
+        seeds = [b"escrow", game.key().as_ref()],
         bump
     )]
-    pub state: Account<'info, State>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (must match state)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub token_escrow: TokenEscrow<'info>,
 }
 
+// settle_by_consent and settle_with_voucher; the signatures are checked
+// through Ed25519Program instructions, so anyone can relay the transaction
 #[derive(Accounts)]
-pub struct CreateGame<'info> {
-    #[account(mut)]
+pub struct SignedSettlement<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
     #[account(
-        init,
-        payer = player1,
-        space = 8 + Game::LEN,
-        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
         bump
     )]
-    pub game: Account<'info, Game>,
+    pub player1_state: Account<'info, PlayerState>,
     #[account(
-        init_if_needed,
-        payer = player1,
-        space = 8 + PlayerState::LEN,
-        seeds = [b"player", player1.key().as_ref()],
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
         bump
     )]
-    pub player_state: Account<'info, PlayerState>,
+    pub player2_state: Account<'info, PlayerState>,
     /// CHECK: Escrow PDA that holds SOL
     #[account(
         mut,
@@ -624,54 +3194,89 @@ pub struct CreateGame<'info> {
         bump
     )]
     pub escrow: AccountInfo<'info>,
-    #[account(mut)]
-    pub player1: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: Treasury account (must match state)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
+    /// CHECK: Instructions sysvar, read for Ed25519Program signatures
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub token_escrow: TokenEscrow<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CreateGameWithDeposit<'info> {
-    #[account(mut)]
+pub struct SubmitResult<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(seeds = [b"oracle_set"], bump)]
+    pub oracle_set: Account<'info, OracleSet>,
     #[account(
-        init,
-        payer = player1,
-        space = 8 + Game::LEN,
-        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        init_if_needed,
+        payer = oracle,
+        space = 8 + ResultAttestations::LEN,
+        seeds = [b"attestations", game.key().as_ref()],
         bump
     )]
-    pub game: Account<'info, Game>,
+    pub attestations: Account<'info, ResultAttestations>,
     #[account(
-        init_if_needed,
-        payer = player1,
-        space = 8 + PlayerState::LEN,
-        seeds = [b"player", player1.key().as_ref()],
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
         bump
     )]
-    pub player_state: Account<'info, PlayerState>,
-    /// CHECK: Escrow PDA that holds SOL (player should have already deposited)
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
+    /// CHECK: Escrow PDA that holds SOL
     #[account(
         mut,
         seeds = [b"escrow", game.key().as_ref()],
         bump
     )]
     pub escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (must match state, paid if this vote settles the game)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
     #[account(mut)]
-    pub player1: Signer<'info>,
+    pub oracle: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub token_escrow: TokenEscrow<'info>,
 }
 
 #[derive(Accounts)]
-pub struct JoinGame<'info> {
-    #[account(mut)]
+pub struct ClaimTimeoutWin<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
     #[account(mut)]
     pub game: Account<'info, Game>,
+    #[account(seeds = [b"game_type".as_ref(), &[game.game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
     #[account(
-        init_if_needed,
-        payer = player2,
-        space = 8 + PlayerState::LEN,
-        seeds = [b"player", player2.key().as_ref()],
+        mut,
+        seeds = [b"player", game.player1.as_ref()],
+        bump
+    )]
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
         bump
     )]
     pub player2_state: Account<'info, PlayerState>,
@@ -682,23 +3287,48 @@ pub struct JoinGame<'info> {
         bump
     )]
     pub escrow: AccountInfo<'info>,
-    #[account(mut)]
-    pub player2: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: Treasury account (must match state)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
+    pub claimer: Signer<'info>,
+    pub token_escrow: TokenEscrow<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CancelGame<'info> {
+pub struct UpdateMoveTime<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
     #[account(mut)]
+    pub game: Account<'info, Game>,
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MakeMove<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
     #[account(mut)]
     pub game: Account<'info, Game>,
+    #[account(seeds = [b"game_type".as_ref(), &[game.game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
     #[account(
         mut,
-        seeds = [b"player", player1.key().as_ref()],
+        seeds = [b"player", game.player1.as_ref()],
         bump
     )]
-    pub player_state: Account<'info, PlayerState>,
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        mut,
+        seeds = [b"player", game.player2.as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
     /// CHECK: Escrow PDA that holds SOL
     #[account(
         mut,
@@ -706,117 +3336,393 @@ pub struct CancelGame<'info> {
         bump
     )]
     pub escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (must match state, paid if this move ends the game)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Player 1 account (must match game)
+    #[account(mut, address = game.player1 @ ErrorCode::NotAPlayer)]
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Player 2 account (must match game)
+    #[account(mut, address = game.player2 @ ErrorCode::NotAPlayer)]
+    pub player2: AccountInfo<'info>,
+    #[account(
+        constraint = player.key() == game.player1 || player.key() == game.player2 @ ErrorCode::NotAPlayer
+    )]
+    pub player: Signer<'info>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+impl<'info> MakeMove<'info> {
+    // Scores the round if the move ended it, and settles the escrow without the
+    // backend authority once the series is decided
+    fn settle_outcome(&mut self, escrow_bump: u8, outcome: Outcome) -> Result<()> {
+        let round_winner = match outcome {
+            Outcome::InProgress => return Ok(()),
+            Outcome::Won(seat) => Some(self.game.player_at(seat)),
+            Outcome::Draw => None,
+        };
+
+        let series = self.game.record_round(&outcome);
+        emit!(RoundCompleted {
+            game_id: self.game.game_id,
+            round: self.game.rounds_played,
+            winner: round_winner,
+            player1_rounds: self.game.player1_rounds,
+            player2_rounds: self.game.player2_rounds,
+        });
+
+        let winner = match series {
+            Outcome::InProgress => {
+                let now = self.state.play_time(Clock::get()?.unix_timestamp);
+                self.game.start_next_round(now);
+                return Ok(());
+            }
+            Outcome::Won(seat) => Some(self.game.player_at(seat)),
+            Outcome::Draw => None,
+        };
+
+        self.finish_game(escrow_bump, winner, FinishReason::Result)
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenBetPool<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    pub game: Account<'info, Game>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BetPool::LEN,
+        seeds = [b"bet_pool", game.key().as_ref()],
+        bump
+    )]
+    pub bet_pool: Account<'info, BetPool>,
+    /// CHECK: Bet escrow PDA that holds the pool's SOL
+    #[account(
+        mut,
+        seeds = [b"bet_escrow", game.key().as_ref()],
+        bump
+    )]
+    pub bet_escrow: AccountInfo<'info>,
     #[account(mut)]
-    pub player1: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AuthorityCancelExpiredGame<'info> {
-    #[account(mut)]
+pub struct PlaceBet<'info> {
+    #[account(seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
+    pub game: Account<'info, Game>,
+    #[account(mut, seeds = [b"bet_pool", game.key().as_ref()], bump)]
+    pub bet_pool: Account<'info, BetPool>,
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + Bet::LEN,
+        seeds = [b"bet", bet_pool.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: Bet escrow PDA that holds the pool's SOL
+    #[account(
+        mut,
+        seeds = [b"bet_escrow", game.key().as_ref()],
+        bump
+    )]
+    pub bet_escrow: AccountInfo<'info>,
     #[account(mut)]
+    pub bettor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBetPool<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
     pub game: Account<'info, Game>,
+    #[account(mut, seeds = [b"bet_pool", game.key().as_ref()], bump)]
+    pub bet_pool: Account<'info, BetPool>,
+    /// CHECK: Bet escrow PDA that holds the pool's SOL
     #[account(
         mut,
-        seeds = [b"player", game.player1.as_ref()],
+        seeds = [b"bet_escrow", game.key().as_ref()],
         bump
     )]
-    pub player_state: Account<'info, PlayerState>,
-    /// CHECK: Escrow PDA that holds SOL
+    pub bet_escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (receives the bet fee)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBetPool<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, close = authority, seeds = [b"bet_pool", bet_pool.game.as_ref()], bump)]
+    pub bet_pool: Account<'info, BetPool>,
+    /// CHECK: Bet escrow PDA that holds the pool's SOL
     #[account(
         mut,
-        seeds = [b"escrow", game.key().as_ref()],
+        seeds = [b"bet_escrow", bet_pool.game.as_ref()],
         bump
     )]
-    pub escrow: AccountInfo<'info>,
-    /// CHECK: Player 1 account (doesn't need to sign for authority cancellation)
+    pub bet_escrow: AccountInfo<'info>,
     #[account(mut)]
-    pub player1: AccountInfo<'info>,
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CompleteGame<'info> {
+pub struct ClaimBet<'info> {
+    #[account(mut, seeds = [b"bet_pool", bet_pool.game.as_ref()], bump)]
+    pub bet_pool: Account<'info, BetPool>,
+    #[account(
+        mut,
+        close = bettor,
+        seeds = [b"bet", bet_pool.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: Bet escrow PDA that holds the pool's SOL
+    #[account(
+        mut,
+        seeds = [b"bet_escrow", bet_pool.game.as_ref()],
+        bump
+    )]
+    pub bet_escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GameOffer<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(
+        constraint = player.key() == game.player1 || player.key() == game.player2 @ ErrorCode::NotAPlayer
+    )]
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitChoice<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
     #[account(mut)]
+    pub game: Account<'info, Game>,
+    #[account(seeds = [b"game_type".as_ref(), &[game.game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGame<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
     pub state: Account<'info, State>,
+    /// CHECK: Legacy Game account, decoded and validated in the instruction
+    #[account(mut, owner = crate::ID)]
+    pub game: UncheckedAccount<'info>,
     #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut, close = player1)]
     pub game: Account<'info, Game>,
+    /// CHECK: Escrow PDA that holds SOL
     #[account(
         mut,
-        seeds = [b"player", game.player1.as_ref()],
+        seeds = [b"escrow", game.key().as_ref()],
         bump
     )]
-    pub player1_state: Account<'info, PlayerState>,
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: The game's bet pool PDA, checked in the instruction if it exists
+    #[account(seeds = [b"bet_pool", game.key().as_ref()], bump)]
+    pub bet_pool: UncheckedAccount<'info>,
+    /// CHECK: Player 1 account (funded the game, receives the rent)
+    #[account(mut, address = game.player1 @ ErrorCode::NotGameCreator)]
+    pub player1: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_escrow: TokenEscrow<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePlayerState<'info> {
     #[account(
         mut,
-        seeds = [b"player", game.player2.as_ref()],
+        close = player,
+        seeds = [b"player", player.key().as_ref()],
         bump
     )]
-    pub player2_state: Account<'info, PlayerState>,
-    /// CHECK: Escrow PDA that holds SOL
+    pub player_state: Account<'info, PlayerState>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", game.key().as_ref()],
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintConfig<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MintConfig::LEN,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOracleSet<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + OracleSet::LEN,
+        seeds = [b"oracle_set"],
         bump
     )]
-    pub escrow: AccountInfo<'info>,
-    /// CHECK: Treasury account (validated in instruction)
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    /// CHECK: Player 1 account
-    #[account(mut)]
-    pub player1: AccountInfo<'info>,
-    /// CHECK: Player 2 account
-    #[account(mut)]
-    pub player2: AccountInfo<'info>,
+    pub oracle_set: Account<'info, OracleSet>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimTimeoutWin<'info> {
-    #[account(mut)]
-    pub state: Account<'info, State>,
-    #[account(mut)]
-    pub game: Account<'info, Game>,
+pub struct UpdateTreasury<'info> {
     #[account(
         mut,
-        seeds = [b"player", game.player1.as_ref()],
-        bump
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
     )]
-    pub player1_state: Account<'info, PlayerState>,
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    pub new_treasury: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
     #[account(
         mut,
-        seeds = [b"player", game.player2.as_ref()],
-        bump
+        seeds = [b"state"],
+        bump,
+        constraint = state.pending_authority == pending_authority.key() @ ErrorCode::NotPendingAuthority
     )]
-    pub player2_state: Account<'info, PlayerState>,
-    /// CHECK: Escrow PDA that holds SOL
+    pub state: Account<'info, State>,
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct InitGameTypeConfig<'info> {
     #[account(
-        mut,
-        seeds = [b"escrow", game.key().as_ref()],
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GameTypeConfig::LEN,
+        seeds = [b"game_type".as_ref(), &[game_type as u8]],
         bump
     )]
-    pub escrow: AccountInfo<'info>,
-    /// CHECK: Treasury account (from state)
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    /// CHECK: Player 1 account
+    pub game_type_config: Account<'info, GameTypeConfig>,
     #[account(mut)]
-    pub player1: AccountInfo<'info>,
-    /// CHECK: Player 2 account
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct UpdateGameTypeConfig<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, seeds = [b"game_type".as_ref(), &[game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Legacy State account, decoded and validated in the instruction
+    #[account(mut, seeds = [b"state"], bump, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
     #[account(mut)]
-    pub player2: AccountInfo<'info>,
-    pub claimer: Signer<'info>,
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMoveTime<'info> {
+pub struct MigratePlayerState<'info> {
+    /// CHECK: Legacy PlayerState account, decoded and validated in the instruction
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub player_state: UncheckedAccount<'info>,
+    /// CHECK: Player the state belongs to (doesn't need to sign)
+    pub player: AccountInfo<'info>,
     #[account(mut)]
-    pub game: Account<'info, Game>,
-    pub player: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 // Events
@@ -825,7 +3731,11 @@ pub struct GameCreated {
     pub game_id: u64,
     pub player1: Pubkey,
     pub wager_amount: u64,
-    pub game_type: u8,
+    pub game_type: GameType,
+    pub rounds_to_win: u8,
+    pub off_chain_rules: bool,
+    pub mint: Pubkey, // Default for native SOL
+    pub invited_player: Option<Pubkey>,
 }
 
 #[event]
@@ -850,6 +3760,234 @@ pub struct GameCompleted {
     pub is_draw: bool,
 }
 
+#[event]
+pub struct MoveMade {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub cell: u8,
+}
+
+#[event]
+pub struct ChoiceCommitted {
+    pub game_id: u64,
+    pub player: Pubkey,
+}
+
+#[event]
+pub struct ChoiceRevealed {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub choice: u8,
+}
+
+#[event]
+pub struct RoundCompleted {
+    pub game_id: u64,
+    pub round: u8,
+    pub winner: Option<Pubkey>, // None for a drawn round
+    pub player1_rounds: u8,
+    pub player2_rounds: u8,
+}
+
+#[event]
+pub struct GameMigrated {
+    pub game_id: u64,
+    pub status: GameStatus,
+    pub off_chain_rules: bool,
+}
+
+#[event]
+pub struct GameClosed {
+    pub game_id: u64,
+    pub rent_refunded: u64,
+    pub escrow_swept: u64,
+}
+
+#[event]
+pub struct ActiveGameLimitUpdated {
+    pub old_limit: u8,
+    pub new_limit: u8,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub old_platform_fee_bps: u16,
+    pub new_platform_fee_bps: u16,
+    pub old_minimum_wager: u64,
+    pub new_minimum_wager: u64,
+    pub old_maximum_wager: u64,
+    pub new_maximum_wager: u64,
+    pub old_move_timeout_seconds: i64,
+    pub new_move_timeout_seconds: i64,
+}
+
+#[event]
+pub struct ResultSignerUpdated {
+    pub old_result_signer: Pubkey,
+    pub new_result_signer: Pubkey,
+}
+
+#[event]
+pub struct DisputeConfigUpdated {
+    pub arbitrator: Pubkey,
+    pub dispute_window_seconds: i64,
+}
+
+#[event]
+pub struct ResultProposed {
+    pub game_id: u64,
+    pub winner: Option<Pubkey>,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct ResultDisputed {
+    pub game_id: u64,
+    pub disputed_by: Pubkey,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub game_id: u64,
+    pub proposed_winner: Option<Pubkey>,
+    pub winner: Option<Pubkey>,
+}
+
+#[event]
+pub struct OracleSetUpdated {
+    pub oracles: Vec<Pubkey>,
+    pub threshold: u8,
+    pub min_wager: u64,
+}
+
+#[event]
+pub struct ResultAttested {
+    pub game_id: u64,
+    pub oracle: Pubkey,
+    pub winner: Option<Pubkey>,
+    pub votes: u8, // Current oracles attesting this same result
+    pub threshold: u8,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub old_paused: u8,
+    pub new_paused: u8,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct GameTypeConfigUpdated {
+    pub game_type: GameType,
+    pub platform_fee_bps: u16,
+    pub minimum_wager: u64,
+    pub maximum_wager: u64,
+    pub move_timeout_seconds: i64,
+    pub waiting_expiry_seconds: i64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct MintConfigUpdated {
+    pub mint: Pubkey,
+    pub minimum_wager: u64,
+    pub maximum_wager: u64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct DrawOffered {
+    pub game_id: u64,
+    pub offered_by: Pubkey,
+}
+
+#[event]
+pub struct AbortOffered {
+    pub game_id: u64,
+    pub offered_by: Pubkey,
+}
+
+#[event]
+pub struct GameAborted {
+    pub game_id: u64,
+    pub player1_refund: u64,
+    pub player2_refund: u64,
+}
+
+#[event]
+pub struct BetFeeUpdated {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+}
+
+#[event]
+pub struct BetPoolOpened {
+    pub game_id: u64,
+    pub cutoff: i64,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct BetPlaced {
+    pub game_id: u64,
+    pub bettor: Pubkey,
+    pub side: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetPoolSettled {
+    pub game_id: u64,
+    pub outcome: BetOutcome,
+    pub payout_pool: u64,
+    pub platform_fee: u64,
+}
+
+#[event]
+pub struct BetClaimed {
+    pub game: Pubkey,
+    pub bettor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetPoolClosed {
+    pub game: Pubkey,
+    pub reserve_returned: u64,
+}
+
+#[event]
+pub struct GameResigned {
+    pub game_id: u64,
+    pub resigned_by: Pubkey,
+    pub winner: Pubkey,
+    pub winner_amount: u64,
+    pub platform_fee: u64,
+}
+
 #[event]
 pub struct GameTimeout {
     pub game_id: u64,
@@ -889,6 +4027,426 @@ pub enum ErrorCode {
     CannotClaimOwnTimeout,
     #[msg("Not your turn")]
     NotYourTurn,
-    #[msg("Player already has an active game")]
+    #[msg("Player already has the maximum number of active games")]
     PlayerAlreadyInGame,
-}
\ No newline at end of file
+    #[msg("Instruction not supported for this game type")]
+    UnsupportedGameType,
+    #[msg("Outcome of this game type is decided on-chain")]
+    OutcomeDecidedOnChain,
+    #[msg("Invalid move")]
+    InvalidMove,
+    #[msg("Cell is already occupied")]
+    CellOccupied,
+    #[msg("Treasury does not match program state")]
+    InvalidTreasury,
+    #[msg("Column is full")]
+    ColumnFull,
+    #[msg("Choice already committed")]
+    AlreadyCommitted,
+    #[msg("Invalid commitment")]
+    InvalidCommitment,
+    #[msg("Both players must commit before revealing")]
+    CommitmentsPending,
+    #[msg("Reveal deadline has passed")]
+    RevealDeadlinePassed,
+    #[msg("Choice already revealed")]
+    AlreadyRevealed,
+    #[msg("Revealed choice does not match commitment")]
+    RevealMismatch,
+    #[msg("Invalid rounds to win")]
+    InvalidRoundsToWin,
+    #[msg("Game is refereed off-chain by the authority")]
+    OffChainRules,
+    #[msg("Game account already uses the current layout")]
+    GameAlreadyMigrated,
+    #[msg("Not a valid game account")]
+    InvalidGameAccount,
+    #[msg("Game is not completed or cancelled")]
+    GameNotFinished,
+    #[msg("Active game limit out of range")]
+    InvalidActiveGameLimit,
+    #[msg("State account already uses the current layout")]
+    StateAlreadyMigrated,
+    #[msg("Not a valid state account")]
+    InvalidStateAccount,
+    #[msg("Player state already uses the current layout")]
+    PlayerStateAlreadyMigrated,
+    #[msg("Not a valid player state account")]
+    InvalidPlayerStateAccount,
+    #[msg("Platform fee too high")]
+    FeeTooHigh,
+    #[msg("Wager limits out of range")]
+    InvalidWagerLimits,
+    #[msg("Move timeout out of range")]
+    InvalidMoveTimeout,
+    #[msg("Waiting game expiry out of range")]
+    InvalidWaitingExpiry,
+    #[msg("Game type is disabled")]
+    GameTypeDisabled,
+    #[msg("Game has not been waiting long enough to expire")]
+    GameNotExpired,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Treasury must hold at least the rent-exempt minimum")]
+    TreasuryNotRentExempt,
+    #[msg("This instruction is paused")]
+    ProgramPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Oracle set must list 1 to 10 distinct oracles")]
+    InvalidOracleSet,
+    #[msg("Oracle threshold out of range")]
+    InvalidOracleThreshold,
+    #[msg("Signer is not in the oracle set")]
+    NotAnOracle,
+    #[msg("Oracle already attested to this game")]
+    AlreadyAttested,
+    #[msg("Game has too many attestations")]
+    TooManyAttestations,
+    #[msg("Wager requires settlement by the oracle set")]
+    OracleSettlementRequired,
+    #[msg("Wager is below the oracle set's minimum")]
+    BelowOracleMinWager,
+    #[msg("Dispute window out of range")]
+    InvalidDisputeWindow,
+    #[msg("A dispute window requires an arbitrator, which can't be cleared once set")]
+    InvalidArbitrator,
+    #[msg("Results must be proposed while a dispute window is configured")]
+    DisputeWindowActive,
+    #[msg("Game has no proposed result")]
+    NoProposedResult,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Game is not disputed")]
+    GameNotDisputed,
+    #[msg("Signer is not the arbitrator")]
+    NotArbitrator,
+    #[msg("Malformed Ed25519 signature instruction")]
+    InvalidSignatureInstruction,
+    #[msg("Both players must sign the result")]
+    MissingPlayerSignature,
+    #[msg("No result signer is configured")]
+    NoResultSigner,
+    #[msg("Result voucher has expired")]
+    VoucherExpired,
+    #[msg("Result voucher is not signed by the result signer")]
+    InvalidVoucher,
+    #[msg("No open offer from the opponent")]
+    NoOpenOffer,
+    #[msg("Game already has moves and can't be aborted")]
+    GameAlreadyStarted,
+    #[msg("Token game is missing a required token account")]
+    MissingTokenAccount,
+    #[msg("Token account does not belong to this game")]
+    InvalidTokenAccount,
+    #[msg("Wagers in this mint are disabled")]
+    MintDisabled,
+    #[msg("Game is reserved for another player")]
+    NotInvitedPlayer,
+    #[msg("Missing or wrong join code")]
+    InvalidJoinCode,
+    #[msg("Tournament capacity must be a power of two between 4 and 16")]
+    InvalidTournamentCapacity,
+    #[msg("Prize splits must cover at most one share per placement and sum to 10000 bps")]
+    InvalidPrizeSplits,
+    #[msg("Tournament is not open for registration")]
+    TournamentNotOpen,
+    #[msg("Player is already registered")]
+    AlreadyRegistered,
+    #[msg("Tournament is full")]
+    TournamentFull,
+    #[msg("Tournament has open seats")]
+    TournamentNotFull,
+    #[msg("Tournament is not in progress")]
+    TournamentNotActive,
+    #[msg("Game is not this match of the tournament's current round")]
+    InvalidTournamentMatch,
+    #[msg("Match has already been created")]
+    MatchAlreadyCreated,
+    #[msg("Tournament has not finished")]
+    TournamentNotFinished,
+    #[msg("Payout has already been claimed")]
+    PayoutAlreadyClaimed,
+    #[msg("Tournament matches are closed when their result is recorded")]
+    TournamentMatch,
+    #[msg("Tournament has progressed too recently to cancel")]
+    TournamentNotStalled,
+    #[msg("Tournament still has open matches or unclaimed payouts")]
+    TournamentNotSettled,
+    #[msg("Betting cutoff must be in the future")]
+    InvalidBetCutoff,
+    #[msg("Betting on this game is closed")]
+    BettingClosed,
+    #[msg("Bet side must be 1 (player1) or 2 (player2)")]
+    InvalidBetSide,
+    #[msg("Players can't bet on their own game")]
+    PlayerCannotBet,
+    #[msg("Existing bet is on the other side")]
+    BetSideMismatch,
+    #[msg("Bet pool is already settled")]
+    BetPoolSettled,
+    #[msg("Bet pool has not been settled")]
+    BetPoolNotSettled,
+    #[msg("Bets on this pool have not all been claimed")]
+    BetsUnclaimed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_type: GameType) -> Game {
+        let mut game = Game::init(
+            0,
+            game_type,
+            Pubkey::new_unique(),
+            0,
+            1,
+            false,
+            Pubkey::default(),
+            0,
+            0,
+        );
+        game.player2 = Pubkey::new_unique();
+        game.status = GameStatus::Active;
+        game
+    }
+
+    #[test]
+    fn moves_out_of_turn_are_rejected() {
+        let mut game = game(GameType::TicTacToe);
+        let (player1, player2) = (game.player1, game.player2);
+
+        assert_eq!(
+            game.play(player2, &Move::Cell(0), 0, 0).err(),
+            Some(ErrorCode::NotYourTurn.into())
+        );
+        game.play(player1, &Move::Cell(0), 0, 0).unwrap();
+        assert_eq!(
+            game.play(player1, &Move::Cell(1), 0, 0).err(),
+            Some(ErrorCode::NotYourTurn.into())
+        );
+        game.play(player2, &Move::Cell(1), 0, 0).unwrap();
+    }
+
+    #[test]
+    fn series_go_to_the_first_player_to_reach_rounds_to_win() {
+        let mut game = game(GameType::TicTacToe);
+        game.rounds_to_win = 2;
+
+        for (round, expected) in [
+            (Outcome::Won(1), None),
+            (Outcome::Draw, None),
+            (Outcome::Won(2), None),
+            (Outcome::Won(2), Some(2)),
+        ] {
+            match game.record_round(&round) {
+                Outcome::InProgress => assert_eq!(expected, None),
+                Outcome::Won(seat) => assert_eq!(expected, Some(seat)),
+                Outcome::Draw => panic!("series can't be drawn"),
+            }
+        }
+        assert_eq!(
+            (game.player1_rounds, game.player2_rounds, game.rounds_played),
+            (1, 2, 4)
+        );
+    }
+
+    #[test]
+    fn drawn_single_rounds_end_board_games_but_replay_rps() {
+        let mut rps = game(GameType::RockPaperScissors);
+        assert!(matches!(
+            rps.record_round(&Outcome::Draw),
+            Outcome::InProgress
+        ));
+        let mut connect_four = game(GameType::ConnectFour);
+        assert!(matches!(
+            connect_four.record_round(&Outcome::Draw),
+            Outcome::Draw
+        ));
+    }
+
+    #[test]
+    fn players_alternate_moving_first_each_round() {
+        let mut game = game(GameType::TicTacToe);
+        game.state[0] = 1;
+        game.move_count = 1;
+        game.rounds_played = 1;
+        game.start_next_round(50);
+        assert_eq!(game.current_turn, game.player2);
+        assert_eq!(game.state, [0; GAME_STATE_SIZE]);
+        assert_eq!((game.move_count, game.last_move_time), (0, 50));
+
+        game.rounds_played = 2;
+        game.start_next_round(60);
+        assert_eq!(game.current_turn, game.player1);
+    }
+
+    #[test]
+    fn private_games_admit_only_the_invited_player_with_the_join_key() {
+        let mut game = game(GameType::TicTacToe);
+        let (invited, join_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        game.set_invitation(Some(invited), Some(join_key)).unwrap();
+
+        let stranger = Pubkey::new_unique();
+        assert_eq!(
+            game.require_can_join(stranger, Some(join_key)).unwrap_err(),
+            ErrorCode::NotInvitedPlayer.into()
+        );
+        assert_eq!(
+            game.require_can_join(invited, None).unwrap_err(),
+            ErrorCode::InvalidJoinCode.into()
+        );
+        assert_eq!(
+            game.require_can_join(invited, Some(stranger)).unwrap_err(),
+            ErrorCode::InvalidJoinCode.into()
+        );
+        game.require_can_join(invited, Some(join_key)).unwrap();
+
+        game.set_invitation(None, None).unwrap();
+        game.require_can_join(stranger, None).unwrap();
+        assert_eq!(
+            game.set_invitation(Some(game.player1), None).unwrap_err(),
+            ErrorCode::CannotJoinOwnGame.into()
+        );
+    }
+
+    #[test]
+    fn active_games_are_capped_at_the_limit() {
+        let mut player_state = PlayerState {
+            player: Pubkey::new_unique(),
+            active_game_ids: Vec::new(),
+            match_game_ids: Vec::new(),
+        };
+        player_state.add_game(1, 2).unwrap();
+        player_state.add_game(2, 2).unwrap();
+        assert_eq!(
+            player_state.add_game(3, 2).unwrap_err(),
+            ErrorCode::PlayerAlreadyInGame.into()
+        );
+
+        player_state.remove_game(1);
+        player_state.add_game(3, 2).unwrap();
+        assert_eq!(player_state.active_game_ids, vec![2, 3]);
+    }
+
+    const RESERVE: u64 = 890_880;
+
+    fn finished_game(winner: Option<u8>) -> Game {
+        let mut game = game(GameType::TicTacToe);
+        game.status = GameStatus::Completed;
+        game.winner = match winner {
+            Some(1) => game.player1,
+            Some(_) => game.player2,
+            None => Pubkey::default(),
+        };
+        game
+    }
+
+    // Places each (side, amount) as a separate bettor's bet
+    fn pool_with(bets: &[(u8, u64)]) -> (BetPool, Vec<Bet>) {
+        let mut pool = BetPool {
+            game: Pubkey::default(),
+            cutoff: 0,
+            fee_bps: 200,
+            player1_total: 0,
+            player2_total: 0,
+            outcome: BetOutcome::Open,
+            payout_pool: 0,
+            player1_bets: 0,
+            player2_bets: 0,
+        };
+        let bets = bets
+            .iter()
+            .map(|&(side, amount)| {
+                if side == 1 {
+                    pool.player1_total += amount;
+                    pool.player1_bets += 1;
+                } else {
+                    pool.player2_total += amount;
+                    pool.player2_bets += 1;
+                }
+                Bet {
+                    pool: Pubkey::default(),
+                    bettor: Pubkey::new_unique(),
+                    side,
+                    amount,
+                }
+            })
+            .collect();
+        (pool, bets)
+    }
+
+    // Claims every bet in order, returning the payouts and what is left in
+    // the escrow after the platform fee and the claims
+    fn claim_all(pool: &mut BetPool, bets: &[Bet], platform_fee: u64) -> (Vec<u64>, u64) {
+        let mut escrow = pool.player1_total + pool.player2_total + RESERVE - platform_fee;
+        let payouts = bets
+            .iter()
+            .map(|bet| {
+                let amount = pool.claim(bet, escrow, RESERVE).unwrap();
+                escrow -= amount;
+                amount
+            })
+            .collect();
+        (payouts, escrow)
+    }
+
+    #[test]
+    fn draws_refund_every_stake() {
+        let (mut pool, bets) = pool_with(&[(1, 3_000_000), (2, 5_000_000)]);
+        assert_eq!(pool.settle(&finished_game(None)), 0);
+        assert!(pool.outcome == BetOutcome::Refund);
+
+        let (payouts, escrow) = claim_all(&mut pool, &bets, 0);
+        assert_eq!(payouts, vec![3_000_000, 5_000_000]);
+        assert_eq!(escrow, RESERVE);
+    }
+
+    #[test]
+    fn one_sided_pools_refund_without_a_fee() {
+        let (mut pool, bets) = pool_with(&[(1, 3_000_000), (1, 2_000_000)]);
+        assert_eq!(pool.settle(&finished_game(Some(1))), 0);
+        assert!(pool.outcome == BetOutcome::Refund);
+
+        let (payouts, escrow) = claim_all(&mut pool, &bets, 0);
+        assert_eq!(payouts, vec![3_000_000, 2_000_000]);
+        assert_eq!(escrow, RESERVE);
+    }
+
+    #[test]
+    fn winners_share_the_pool_in_proportion_to_their_stakes() {
+        let (mut pool, bets) = pool_with(&[
+            (2, 1_000_000),
+            (1, 1_000_000),
+            (2, 2_000_000),
+            (1, 3_000_000),
+        ]);
+        let platform_fee = pool.settle(&finished_game(Some(2)));
+        assert_eq!(platform_fee, 140_000);
+        assert_eq!(pool.payout_pool, 6_860_000);
+
+        // 6_860_000 / 3 doesn't divide evenly: the last winner takes the
+        // rounding dust, and the losing bets that claim after it get nothing
+        let (payouts, escrow) = claim_all(&mut pool, &bets, platform_fee);
+        assert_eq!(payouts, vec![2_286_666, 0, 4_573_334, 0]);
+        assert_eq!(escrow, RESERVE);
+        assert_eq!(pool.player1_bets + pool.player2_bets, 0);
+    }
+
+    #[test]
+    fn bets_wait_for_the_pool_to_settle() {
+        let (mut pool, bets) = pool_with(&[(1, 1_000_000)]);
+        assert_eq!(
+            pool.claim(&bets[0], RESERVE + 1_000_000, RESERVE)
+                .unwrap_err(),
+            ErrorCode::BetPoolNotSettled.into()
+        );
+    }
+}
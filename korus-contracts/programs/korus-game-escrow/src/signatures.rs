@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+
+use crate::ErrorCode;
+
+// Layout of Ed25519Program instruction data: a count and padding byte, then
+// one 14-byte offsets entry per signature
+const HEADER_LEN: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+// Instruction index meaning "this Ed25519Program instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Public keys whose signature over `message` was verified by an
+// Ed25519Program instruction in the current transaction. The runtime rejects
+// the whole transaction if any of those signatures is invalid, so finding an
+// entry here is proof the key signed. Only entries whose key, signature and
+// message live inside the Ed25519Program instruction itself are counted.
+pub fn verified_signers(instructions: &AccountInfo, message: &[u8]) -> Result<Vec<Pubkey>> {
+    let mut signers = Vec::new();
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        index += 1;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+
+        let data = &ix.data;
+        require!(data.len() >= HEADER_LEN, ErrorCode::InvalidSignatureInstruction);
+        for entry in 0..data[0] as usize {
+            let start = HEADER_LEN + entry * OFFSETS_LEN;
+            let offsets = data
+                .get(start..start + OFFSETS_LEN)
+                .ok_or(ErrorCode::InvalidSignatureInstruction)?;
+            let field = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+            let (signature_ix, pubkey_offset, pubkey_ix) = (field(1), field(2), field(3));
+            let (message_offset, message_size, message_ix) = (field(4), field(5), field(6));
+            if [signature_ix, pubkey_ix, message_ix] != [CURRENT_INSTRUCTION; 3] {
+                continue;
+            }
+
+            let pubkey_start = pubkey_offset as usize;
+            let message_start = message_offset as usize;
+            let pubkey = data
+                .get(pubkey_start..pubkey_start + PUBKEY_LEN)
+                .ok_or(ErrorCode::InvalidSignatureInstruction)?;
+            let signed = data
+                .get(message_start..message_start + message_size as usize)
+                .ok_or(ErrorCode::InvalidSignatureInstruction)?;
+            if signed == message {
+                signers.push(Pubkey::new_from_array(pubkey.try_into().unwrap()));
+            }
+        }
+    }
+    Ok(signers)
+}
+
+// Message players (or the result signer) sign to attest a game's result:
+// domain tag, program id, game_id (LE) and the winner (default for a draw)
+pub fn result_message(domain: &[u8], game_id: u64, winner: Option<Pubkey>) -> Vec<u8> {
+    [
+        domain,
+        crate::ID.as_ref(),
+        &game_id.to_le_bytes(),
+        winner.unwrap_or_default().as_ref(),
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedInstruction, ID as INSTRUCTIONS_ID,
+    };
+
+    const SIGNATURE_LEN: usize = 64;
+
+    // One-signature Ed25519Program data laid out the way the web3.js helper
+    // does it: offsets, then pubkey, signature and message
+    fn ed25519_data(pubkey: &Pubkey, message: &[u8], instruction: u16) -> Vec<u8> {
+        let pubkey_offset = HEADER_LEN + OFFSETS_LEN;
+        let signature_offset = pubkey_offset + PUBKEY_LEN;
+        let message_offset = signature_offset + SIGNATURE_LEN;
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset as u16,
+            instruction,
+            pubkey_offset as u16,
+            instruction,
+            message_offset as u16,
+            message.len() as u16,
+            instruction,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey.as_ref());
+        data.extend_from_slice(&[0; SIGNATURE_LEN]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn signers_in(
+        key: Pubkey,
+        instructions: &[(Pubkey, Vec<u8>)],
+        message: &[u8],
+    ) -> Result<Vec<Pubkey>> {
+        let borrowed: Vec<_> = instructions
+            .iter()
+            .map(|(program_id, data)| BorrowedInstruction {
+                program_id,
+                accounts: Vec::new(),
+                data,
+            })
+            .collect();
+        let mut data = construct_instructions_data(&borrowed);
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        verified_signers(&account, message)
+    }
+
+    fn signers(instructions: &[(Pubkey, Vec<u8>)], message: &[u8]) -> Result<Vec<Pubkey>> {
+        signers_in(INSTRUCTIONS_ID, instructions, message)
+    }
+
+    #[test]
+    fn finds_the_key_that_signed_the_message() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_data(&signer, b"result", CURRENT_INSTRUCTION);
+        let other = (Pubkey::new_unique(), ix.clone());
+
+        let found = signers(&[other, (ed25519_program::ID, ix)], b"result").unwrap();
+        assert_eq!(found, vec![signer]);
+    }
+
+    #[test]
+    fn signatures_over_another_message_are_ignored() {
+        let ix = ed25519_data(&Pubkey::new_unique(), b"result", CURRENT_INSTRUCTION);
+        let ed25519 = [(ed25519_program::ID, ix)];
+
+        assert!(signers(&ed25519, b"other").unwrap().is_empty());
+        assert!(signers(&ed25519, b"resul").unwrap().is_empty());
+    }
+
+    #[test]
+    fn entries_pointing_at_other_instructions_are_ignored() {
+        // The key and message could come from any instruction, signed or not
+        let ix = ed25519_data(&Pubkey::new_unique(), b"result", 0);
+        assert!(signers(&[(ed25519_program::ID, ix)], b"result")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn only_the_instructions_sysvar_is_read() {
+        let ix = ed25519_data(&Pubkey::new_unique(), b"result", CURRENT_INSTRUCTION);
+        let forged = signers_in(
+            Pubkey::new_unique(),
+            &[(ed25519_program::ID, ix)],
+            b"result",
+        );
+        assert!(forged.unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_data_is_rejected() {
+        let valid = ed25519_data(&Pubkey::new_unique(), b"result", CURRENT_INSTRUCTION);
+        let pubkey_offset = HEADER_LEN + 4;
+        let message_offset = HEADER_LEN + 8;
+
+        let mut past_the_end = valid.clone();
+        past_the_end[pubkey_offset..pubkey_offset + 2]
+            .copy_from_slice(&(valid.len() as u16 - 16).to_le_bytes());
+        let mut long_message = valid.clone();
+        long_message[message_offset..message_offset + 2]
+            .copy_from_slice(&(valid.len() as u16).to_le_bytes());
+
+        for data in [
+            vec![1],
+            vec![1, 0],
+            valid[..HEADER_LEN + OFFSETS_LEN - 1].to_vec(),
+            valid[..valid.len() - 1].to_vec(),
+            past_the_end,
+            long_message,
+        ] {
+            assert_eq!(
+                signers(&[(ed25519_program::ID, data)], b"result").unwrap_err(),
+                ErrorCode::InvalidSignatureInstruction.into()
+            );
+        }
+    }
+}
@@ -0,0 +1,984 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::system_instruction;
+
+use crate::games::GameType;
+use crate::{
+    grow_account, require_bet_pool_settled, transfer_from_escrow, ErrorCode, Game, GameStatus,
+    GameTypeConfig, PlayerState, State, MAX_ROUNDS_TO_WIN, PAUSE_CREATE, PAUSE_JOIN, PAUSE_SETTLE,
+};
+
+// Tournaments are single-elimination brackets over a power-of-two field
+const MIN_TOURNAMENT_PLAYERS: u8 = 4;
+const MAX_TOURNAMENT_PLAYERS: u8 = 16;
+// Placement groups with a prize: champion, runner-up, then the losers of
+// each earlier round (semi-finals, quarter-finals, round of 16)
+const MAX_PRIZE_PLACES: usize = 5;
+// How long a bracket may go without progress before anyone can cancel it
+const TOURNAMENT_STALL_SECONDS: i64 = 604_800; // 7 days
+
+// Opens a single-elimination tournament. Entry fees are escrowed until
+// the final, then the pool minus the platform fee is split by placement.
+pub fn create(
+    ctx: Context<CreateTournament>,
+    game_type: GameType,
+    entry_fee: u64,
+    capacity: u8,
+    rounds_to_win: u8,
+    prize_splits: Vec<u16>,
+) -> Result<()> {
+    let config = &ctx.accounts.game_type_config;
+    require!(config.enabled, ErrorCode::GameTypeDisabled);
+    require!(config.wager_in_range(entry_fee), ErrorCode::InvalidWagerAmount);
+    require!(
+        capacity.is_power_of_two()
+            && (MIN_TOURNAMENT_PLAYERS..=MAX_TOURNAMENT_PLAYERS).contains(&capacity),
+        ErrorCode::InvalidTournamentCapacity
+    );
+    require!(
+        (1..=MAX_ROUNDS_TO_WIN).contains(&rounds_to_win),
+        ErrorCode::InvalidRoundsToWin
+    );
+
+    let state = &mut ctx.accounts.state;
+    let tournament = &mut ctx.accounts.tournament;
+    tournament.tournament_id = state.total_tournaments;
+    tournament.game_type = game_type;
+    tournament.entry_fee = entry_fee;
+    tournament.capacity = capacity;
+    tournament.rounds_to_win = rounds_to_win;
+    tournament.platform_fee_bps = config.platform_fee_bps;
+    tournament.prize_splits = prize_splits;
+    tournament.status = TournamentStatus::Registration;
+    tournament.players = Vec::new();
+    tournament.eliminated_in = Vec::new();
+    tournament.round = 0;
+    tournament.matches = Vec::new();
+    tournament.replays = Vec::new();
+    tournament.prize_pool = 0;
+    tournament.claimed = 0;
+    tournament.created_at = Clock::get()?.unix_timestamp;
+    tournament.last_progress_at = tournament.created_at;
+    let total_splits: u32 = tournament.prize_splits.iter().map(|&bps| bps as u32).sum();
+    require!(
+        !tournament.prize_splits.is_empty()
+            && tournament.prize_splits.len() <= tournament.total_rounds() as usize + 1
+            && total_splits == 10_000,
+        ErrorCode::InvalidPrizeSplits
+    );
+
+    state.total_tournaments += 1;
+
+    // Keeps the escrow rent-exempt while it holds entry fees;
+    // close_tournament returns it to the authority
+    let reserve = Rent::get()?.minimum_balance(0);
+    let ix = system_instruction::transfer(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.tournament_escrow.key(),
+        reserve,
+    );
+    anchor_lang::solana_program::program::invoke(
+        &ix,
+        &[
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.tournament_escrow.to_account_info(),
+        ],
+    )?;
+
+    emit!(TournamentCreated {
+        tournament_id: tournament.tournament_id,
+        game_type,
+        entry_fee,
+        capacity,
+        rounds_to_win,
+        prize_splits: tournament.prize_splits.clone(),
+    });
+
+    Ok(())
+}
+
+pub fn register(ctx: Context<RegisterForTournament>) -> Result<()> {
+    ctx.accounts.state.require_unpaused(PAUSE_JOIN)?;
+    let tournament = &mut ctx.accounts.tournament;
+    let player = ctx.accounts.player.key();
+
+    require!(
+        tournament.status == TournamentStatus::Registration,
+        ErrorCode::TournamentNotOpen
+    );
+    require!(!tournament.players.contains(&player), ErrorCode::AlreadyRegistered);
+    require!(
+        tournament.players.len() < tournament.capacity as usize,
+        ErrorCode::TournamentFull
+    );
+
+    tournament.players.push(player);
+    tournament.eliminated_in.push(0);
+    tournament.last_progress_at = Clock::get()?.unix_timestamp;
+    ctx.accounts.state.total_volume += tournament.entry_fee;
+
+    let ix = system_instruction::transfer(
+        &player,
+        &ctx.accounts.tournament_escrow.key(),
+        tournament.entry_fee,
+    );
+    anchor_lang::solana_program::program::invoke(
+        &ix,
+        &[
+            ctx.accounts.player.to_account_info(),
+            ctx.accounts.tournament_escrow.to_account_info(),
+        ],
+    )?;
+
+    emit!(TournamentRegistered {
+        tournament_id: tournament.tournament_id,
+        player,
+        players: tournament.players.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Permissionless once every seat is taken: shuffles the field into the
+// bracket and opens the first round
+pub fn start(ctx: Context<StartTournament>) -> Result<()> {
+    let tournament = &mut ctx.accounts.tournament;
+    require!(
+        tournament.status == TournamentStatus::Registration,
+        ErrorCode::TournamentNotOpen
+    );
+    require!(
+        tournament.players.len() == tournament.capacity as usize,
+        ErrorCode::TournamentNotFull
+    );
+
+    // Fisher-Yates over a hash chain. The slot is known to the leader, so
+    // this only keeps registration order from deciding the pairings.
+    let clock = Clock::get()?;
+    let tournament_key = tournament.key();
+    let mut seed = hashv(&[tournament_key.as_ref(), &clock.slot.to_le_bytes()]).to_bytes();
+    for i in (1..tournament.players.len()).rev() {
+        seed = hashv(&[&seed]).to_bytes();
+        let roll = u64::from_le_bytes(seed[..8].try_into().unwrap());
+        tournament.players.swap(i, (roll % (i as u64 + 1)) as usize);
+    }
+
+    tournament.status = TournamentStatus::Active;
+    tournament.round = 1;
+    tournament.last_progress_at = clock.unix_timestamp;
+    tournament.matches = vec![Pubkey::default(); tournament.players.len() / 2];
+    tournament.replays = vec![0; tournament.matches.len()];
+
+    emit!(TournamentStarted {
+        tournament_id: tournament.tournament_id,
+        bracket: tournament.players.clone(),
+    });
+
+    Ok(())
+}
+
+// Creates the game for one pairing of the current round. Matches are
+// ordinary unwagered games, so they are played and settled through the
+// same instructions as any other game. Each replay of a drawn match swaps
+// who moves first.
+pub fn create_match(
+    ctx: Context<CreateTournamentMatch>,
+    match_index: u8,
+) -> Result<()> {
+    ctx.accounts.state.require_unpaused(PAUSE_CREATE)?;
+    let tournament = &mut ctx.accounts.tournament;
+    require!(
+        tournament.status == TournamentStatus::Active,
+        ErrorCode::TournamentNotActive
+    );
+    let (seat1, seat2) = tournament.pairing(match_index)?;
+    let (first, second) = if tournament.replays[match_index as usize] % 2 == 1 {
+        (seat2, seat1)
+    } else {
+        (seat1, seat2)
+    };
+    require!(
+        tournament.matches[match_index as usize] == Pubkey::default()
+            && tournament.eliminated_in[seat1] == 0
+            && tournament.eliminated_in[seat2] == 0,
+        ErrorCode::MatchAlreadyCreated
+    );
+    require_keys_eq!(
+        ctx.accounts.player1.key(),
+        tournament.players[first],
+        ErrorCode::NotAPlayer
+    );
+    require_keys_eq!(
+        ctx.accounts.player2.key(),
+        tournament.players[second],
+        ErrorCode::NotAPlayer
+    );
+
+    let state = &mut ctx.accounts.state;
+    let game = &mut ctx.accounts.game;
+    let clock = Clock::get()?;
+
+    game.set_inner(Game::init(
+        state.total_games,
+        tournament.game_type,
+        ctx.accounts.player1.key(),
+        0,
+        tournament.rounds_to_win,
+        false,
+        Pubkey::default(),
+        0, // No pot; the tournament takes its fee from the prize pool
+        clock.unix_timestamp,
+    ));
+    game.player2 = ctx.accounts.player2.key();
+    game.status = GameStatus::Active;
+    game.last_move_time = state.play_time(clock.unix_timestamp);
+    game.tournament = tournament.key();
+
+    // Matches keep the player states open until they settle, but are kept
+    // apart from the active game limit so one busy player can't hold up the
+    // bracket, nor a match stop its players creating or joining games. The
+    // authority pays to grow an account that no longer fits.
+    let authority = ctx.accounts.authority.to_account_info();
+    for (player_state, player) in [
+        (&mut ctx.accounts.player1_state, game.player1),
+        (&mut ctx.accounts.player2_state, game.player2),
+    ] {
+        player_state.player = player;
+        player_state.add_match(game.game_id);
+        let info = player_state.to_account_info();
+        if info.data_len() < player_state.space() {
+            grow_account(&info, &authority, player_state.space())?;
+        }
+    }
+
+    tournament.matches[match_index as usize] = game.key();
+    tournament.last_progress_at = clock.unix_timestamp;
+    state.total_games += 1;
+    state.active_games += 1;
+
+    emit!(TournamentMatchCreated {
+        tournament_id: tournament.tournament_id,
+        round: tournament.round,
+        match_index,
+        game_id: game.game_id,
+        player1: game.player1,
+        player2: game.player2,
+    });
+
+    Ok(())
+}
+
+// Permissionless: advances the winner of a finished match and closes its
+// game. A drawn or aborted match is reopened for a replay. The last result
+// of the final pays the platform fee and opens prize claims. Matches still
+// running when a tournament is cancelled are only closed.
+pub fn record_match(
+    ctx: Context<RecordTournamentMatch>,
+    match_index: u8,
+) -> Result<()> {
+    ctx.accounts.state.require_unpaused(PAUSE_SETTLE)?;
+    let tournament = &mut ctx.accounts.tournament;
+    let game = &ctx.accounts.game;
+    require!(
+        tournament.status == TournamentStatus::Active
+            || tournament.status == TournamentStatus::Cancelled,
+        ErrorCode::TournamentNotActive
+    );
+    let (seat1, seat2) = tournament.pairing(match_index)?;
+    require!(
+        tournament.matches[match_index as usize] == game.key(),
+        ErrorCode::InvalidTournamentMatch
+    );
+    require!(
+        game.status == GameStatus::Completed || game.status == GameStatus::Cancelled,
+        ErrorCode::GameNotFinished
+    );
+    require_bet_pool_settled(&ctx.accounts.bet_pool)?;
+
+    let round = tournament.round;
+    tournament.matches[match_index as usize] = Pubkey::default();
+    if tournament.status == TournamentStatus::Cancelled {
+        return Ok(());
+    }
+    tournament.last_progress_at = Clock::get()?.unix_timestamp;
+
+    if game.winner == Pubkey::default() {
+        let replays = tournament.replays[match_index as usize].wrapping_add(1);
+        tournament.replays[match_index as usize] = replays;
+
+        emit!(TournamentMatchDrawn {
+            tournament_id: tournament.tournament_id,
+            round,
+            match_index,
+            replays,
+        });
+
+        return Ok(());
+    }
+
+    let (winner, loser) = if game.winner == tournament.players[seat2] {
+        (seat2, seat1)
+    } else {
+        (seat1, seat2)
+    };
+    tournament.eliminated_in[loser] = round;
+
+    emit!(TournamentMatchRecorded {
+        tournament_id: tournament.tournament_id,
+        round,
+        match_index,
+        winner: tournament.players[winner],
+        loser: tournament.players[loser],
+    });
+
+    let losers = tournament
+        .eliminated_in
+        .iter()
+        .filter(|&&eliminated| eliminated == round)
+        .count();
+    if losers < tournament.round_players().len() / 2 {
+        return Ok(());
+    }
+
+    if round < tournament.total_rounds() {
+        tournament.round += 1;
+        tournament.matches = vec![Pubkey::default(); tournament.round_players().len() / 2];
+        tournament.replays = vec![0; tournament.matches.len()];
+        return Ok(());
+    }
+
+    let pot = tournament.entry_fee * tournament.capacity as u64;
+    let platform_fee = (pot * tournament.platform_fee_bps as u64) / 10000;
+    tournament.prize_pool = pot - platform_fee;
+    tournament.status = TournamentStatus::Completed;
+
+    if platform_fee > 0 {
+        let tournament_key = tournament.key();
+        let seeds = &[
+            b"tournament_escrow",
+            tournament_key.as_ref(),
+            &[ctx.bumps.tournament_escrow],
+        ];
+        transfer_from_escrow(
+            &ctx.accounts.tournament_escrow,
+            &ctx.accounts.treasury,
+            platform_fee,
+            &[&seeds[..]],
+        )?;
+    }
+
+    emit!(TournamentCompleted {
+        tournament_id: tournament.tournament_id,
+        champion: tournament.players[winner],
+        prize_pool: tournament.prize_pool,
+        platform_fee,
+    });
+
+    Ok(())
+}
+
+// Only before the bracket starts; players then reclaim their entry fee
+// with claim_tournament_payout. Once it has started, only
+// cancel_stalled_tournament can stop it.
+pub fn cancel(ctx: Context<CancelTournament>) -> Result<()> {
+    let tournament = &mut ctx.accounts.tournament;
+    require!(
+        tournament.status == TournamentStatus::Registration,
+        ErrorCode::TournamentNotOpen
+    );
+    tournament.status = TournamentStatus::Cancelled;
+
+    emit!(TournamentCancelled {
+        tournament_id: tournament.tournament_id,
+        players: tournament.players.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Permissionless, so entry fees can't be locked in a bracket nobody drives:
+// once registration or play has stalled for TOURNAMENT_STALL_SECONDS,
+// anyone can cancel the tournament and players reclaim their entry fees
+pub fn cancel_stalled(ctx: Context<CancelStalledTournament>) -> Result<()> {
+    let tournament = &mut ctx.accounts.tournament;
+    require!(
+        tournament.status == TournamentStatus::Registration
+            || tournament.status == TournamentStatus::Active,
+        ErrorCode::TournamentNotActive
+    );
+    require!(
+        Clock::get()?.unix_timestamp - tournament.last_progress_at > TOURNAMENT_STALL_SECONDS,
+        ErrorCode::TournamentNotStalled
+    );
+    tournament.status = TournamentStatus::Cancelled;
+
+    emit!(TournamentCancelled {
+        tournament_id: tournament.tournament_id,
+        players: tournament.players.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Pays a player their placement's share of a completed tournament, or
+// their entry fee back from a cancelled one
+pub fn claim_payout(ctx: Context<ClaimTournamentPayout>) -> Result<()> {
+    let tournament = &mut ctx.accounts.tournament;
+    let index = tournament
+        .players
+        .iter()
+        .position(|&player| player == ctx.accounts.player.key())
+        .ok_or(ErrorCode::NotAPlayer)?;
+    require!(
+        tournament.status == TournamentStatus::Completed
+            || tournament.status == TournamentStatus::Cancelled,
+        ErrorCode::TournamentNotFinished
+    );
+
+    let escrow = &ctx.accounts.tournament_escrow;
+    let reserve = Rent::get()?.minimum_balance(0);
+    let amount = tournament.claim(index, escrow.lamports(), reserve)?;
+
+    if amount > 0 {
+        let tournament_key = tournament.key();
+        let seeds = &[
+            b"tournament_escrow",
+            tournament_key.as_ref(),
+            &[ctx.bumps.tournament_escrow],
+        ];
+        transfer_from_escrow(
+            escrow,
+            &ctx.accounts.player.to_account_info(),
+            amount,
+            &[&seeds[..]],
+        )?;
+    }
+
+    emit!(TournamentPayoutClaimed {
+        tournament_id: tournament.tournament_id,
+        player: ctx.accounts.player.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+// Once every match is closed and every payout claimed, returns the rent
+// reserve and the tournament's own rent to the authority that paid them
+pub fn close(ctx: Context<CloseTournament>) -> Result<()> {
+    let tournament = &ctx.accounts.tournament;
+    require!(
+        tournament.status == TournamentStatus::Completed
+            || tournament.status == TournamentStatus::Cancelled,
+        ErrorCode::TournamentNotFinished
+    );
+    require!(
+        tournament.matches.iter().all(|&game| game == Pubkey::default())
+            && !tournament.outstanding(),
+        ErrorCode::TournamentNotSettled
+    );
+
+    let escrow = &ctx.accounts.tournament_escrow;
+    let reserve_returned = escrow.lamports();
+    if reserve_returned > 0 {
+        let tournament_key = tournament.key();
+        let seeds = &[
+            b"tournament_escrow",
+            tournament_key.as_ref(),
+            &[ctx.bumps.tournament_escrow],
+        ];
+        transfer_from_escrow(
+            escrow,
+            &ctx.accounts.authority.to_account_info(),
+            reserve_returned,
+            &[&seeds[..]],
+        )?;
+    }
+
+    emit!(TournamentClosed {
+        tournament_id: tournament.tournament_id,
+        reserve_returned,
+    });
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentStatus {
+    Registration,
+    Active,
+    Completed,
+    Cancelled,
+}
+
+#[account]
+pub struct Tournament {
+    pub tournament_id: u64,
+    pub game_type: GameType,
+    pub entry_fee: u64,
+    pub capacity: u8,
+    pub rounds_to_win: u8,      // Per match
+    pub platform_fee_bps: u16,  // Game type's fee when the tournament was created
+    pub prize_splits: Vec<u16>, // Share of the prize pool per placement group, in bps
+    pub status: TournamentStatus,
+    pub players: Vec<Pubkey>,   // Registration order, bracket order once started
+    pub eliminated_in: Vec<u8>, // Round each player lost in, parallel to players; 0 while still in
+    pub round: u8,              // Current round, starting at 1
+    pub matches: Vec<Pubkey>,   // Current round's match games; default until created or once recorded
+    pub replays: Vec<u8>,       // Times each current-round match has been drawn, parallel to matches
+    pub prize_pool: u64,        // Entry fees minus the platform fee, set when the final is recorded
+    pub claimed: u16,           // Bit per player index already paid out
+    pub created_at: i64,
+    pub last_progress_at: i64,  // Last registration, start, match created or result recorded
+}
+
+impl Tournament {
+    pub const LEN: usize = 8 + 1 + 8 + 1 + 1 + 2
+        + (4 + 2 * MAX_PRIZE_PLACES)
+        + 1
+        + (4 + 32 * MAX_TOURNAMENT_PLAYERS as usize)
+        + (4 + MAX_TOURNAMENT_PLAYERS as usize)
+        + 1
+        + (4 + 32 * (MAX_TOURNAMENT_PLAYERS as usize / 2))
+        + (4 + MAX_TOURNAMENT_PLAYERS as usize / 2)
+        + 8 + 2 + 8 + 8;
+
+    fn total_rounds(&self) -> u8 {
+        self.capacity.trailing_zeros() as u8
+    }
+
+    // Indexes of the players who started the current round, in bracket order
+    fn round_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|&i| self.eliminated_in[i] == 0 || self.eliminated_in[i] == self.round)
+            .collect()
+    }
+
+    // Players of a current-round match: neighbours in the bracket
+    fn pairing(&self, match_index: u8) -> Result<(usize, usize)> {
+        let players = self.round_players();
+        let first = 2 * match_index as usize;
+        require!(first + 1 < players.len(), ErrorCode::InvalidTournamentMatch);
+        Ok((players[first], players[first + 1]))
+    }
+
+    // Group 0 is the champion, group 1 the runner-up, and group g the
+    // 2^(g-1) players knocked out g-1 rounds before the final
+    fn prize_share(&self, index: usize) -> u64 {
+        let group = match self.eliminated_in[index] {
+            0 => 0,
+            round => (self.total_rounds() - round + 1) as usize,
+        };
+        let Some(&split_bps) = self.prize_splits.get(group) else {
+            return 0;
+        };
+        let group_size = if group == 0 { 1 } else { 1u64 << (group - 1) };
+        self.prize_pool * split_bps as u64 / 10000 / group_size
+    }
+
+    // What a player is owed once the tournament is completed or cancelled
+    fn payout(&self, index: usize) -> u64 {
+        match self.status {
+            TournamentStatus::Completed => self.prize_share(index),
+            TournamentStatus::Cancelled => self.entry_fee,
+            _ => 0,
+        }
+    }
+
+    // Whether any player is still owed a payout
+    fn outstanding(&self) -> bool {
+        (0..self.players.len()).any(|i| self.claimed & (1 << i) == 0 && self.payout(i) > 0)
+    }
+
+    // Marks a player paid and returns the amount to send them. Earlier
+    // claims take exactly their payout; the last player still owed anything
+    // also takes the rounding dust, leaving only the rent reserve.
+    fn claim(&mut self, index: usize, escrow_balance: u64, reserve: u64) -> Result<u64> {
+        require!(
+            self.claimed & (1 << index) == 0,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+        self.claimed |= 1 << index;
+
+        if self.outstanding() || self.payout(index) == 0 {
+            Ok(self.payout(index))
+        } else {
+            Ok(escrow_balance.saturating_sub(reserve))
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct CreateTournament<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(seeds = [b"game_type".as_ref(), &[game_type as u8]], bump)]
+    pub game_type_config: Account<'info, GameTypeConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Tournament::LEN,
+        seeds = [b"tournament", state.total_tournaments.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+    /// CHECK: Tournament escrow PDA that holds the entry fees
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub tournament_escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterForTournament<'info> {
+    #[account(mut, seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    /// CHECK: Tournament escrow PDA that holds the entry fees
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub tournament_escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartTournament<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTournamentMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Game::LEN,
+        seeds = [b"game", state.total_games.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player1.key().as_ref()],
+        bump
+    )]
+    pub player1_state: Account<'info, PlayerState>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PlayerState::LEN,
+        seeds = [b"player", player2.key().as_ref()],
+        bump
+    )]
+    pub player2_state: Account<'info, PlayerState>,
+    /// CHECK: Checked against the bracket in the instruction
+    pub player1: AccountInfo<'info>,
+    /// CHECK: Checked against the bracket in the instruction
+    pub player2: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordTournamentMatch<'info> {
+    #[account(seeds = [b"state"], bump)]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    // The authority paid for the match in create_tournament_match
+    #[account(mut, close = authority)]
+    pub game: Account<'info, Game>,
+    /// CHECK: The match's bet pool PDA, checked in the instruction if it exists
+    #[account(seeds = [b"bet_pool", game.key().as_ref()], bump)]
+    pub bet_pool: UncheckedAccount<'info>,
+    /// CHECK: Tournament escrow PDA that holds the entry fees
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub tournament_escrow: AccountInfo<'info>,
+    /// CHECK: Treasury account (receives the platform fee)
+    #[account(mut, address = state.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Backend authority, receives the match game's rent
+    #[account(mut, address = state.authority @ ErrorCode::UnauthorizedCaller)]
+    pub authority: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTournament<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStalledTournament<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTournament<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump,
+        has_one = authority @ ErrorCode::UnauthorizedCaller
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, close = authority)]
+    pub tournament: Account<'info, Tournament>,
+    /// CHECK: Tournament escrow PDA that holds the entry fees
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub tournament_escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTournamentPayout<'info> {
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    /// CHECK: Tournament escrow PDA that holds the entry fees
+    #[account(
+        mut,
+        seeds = [b"tournament_escrow", tournament.key().as_ref()],
+        bump
+    )]
+    pub tournament_escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct TournamentCreated {
+    pub tournament_id: u64,
+    pub game_type: GameType,
+    pub entry_fee: u64,
+    pub capacity: u8,
+    pub rounds_to_win: u8,
+    pub prize_splits: Vec<u16>,
+}
+
+#[event]
+pub struct TournamentRegistered {
+    pub tournament_id: u64,
+    pub player: Pubkey,
+    pub players: u8,
+}
+
+#[event]
+pub struct TournamentStarted {
+    pub tournament_id: u64,
+    pub bracket: Vec<Pubkey>,
+}
+
+#[event]
+pub struct TournamentMatchCreated {
+    pub tournament_id: u64,
+    pub round: u8,
+    pub match_index: u8,
+    pub game_id: u64,
+    pub player1: Pubkey,
+    pub player2: Pubkey,
+}
+
+#[event]
+pub struct TournamentMatchRecorded {
+    pub tournament_id: u64,
+    pub round: u8,
+    pub match_index: u8,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+}
+
+#[event]
+pub struct TournamentMatchDrawn {
+    pub tournament_id: u64,
+    pub round: u8,
+    pub match_index: u8,
+    pub replays: u8,
+}
+
+#[event]
+pub struct TournamentCompleted {
+    pub tournament_id: u64,
+    pub champion: Pubkey,
+    pub prize_pool: u64,
+    pub platform_fee: u64,
+}
+
+#[event]
+pub struct TournamentCancelled {
+    pub tournament_id: u64,
+    pub players: u8,
+}
+
+#[event]
+pub struct TournamentPayoutClaimed {
+    pub tournament_id: u64,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TournamentClosed {
+    pub tournament_id: u64,
+    pub reserve_returned: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE: u64 = 890_880;
+
+    // A finished four-player bracket: player 0 won the final against
+    // player 1, players 2 and 3 went out in the semi-finals
+    fn completed() -> Tournament {
+        let prize_pool = 3_920_001;
+        Tournament {
+            tournament_id: 0,
+            game_type: GameType::TicTacToe,
+            entry_fee: 1_000_000,
+            capacity: 4,
+            rounds_to_win: 1,
+            platform_fee_bps: 200,
+            prize_splits: vec![6000, 3000, 1000],
+            status: TournamentStatus::Completed,
+            players: (0..4).map(|_| Pubkey::new_unique()).collect(),
+            eliminated_in: vec![0, 2, 1, 1],
+            round: 2,
+            matches: Vec::new(),
+            replays: Vec::new(),
+            prize_pool,
+            claimed: 0,
+            created_at: 0,
+            last_progress_at: 0,
+        }
+    }
+
+    #[test]
+    fn placements_claim_after_the_champion() {
+        let mut tournament = completed();
+        let mut escrow = tournament.prize_pool + RESERVE;
+
+        for (index, expected) in [(0, 2_352_000), (1, 1_176_000), (2, 196_000)] {
+            let amount = tournament.claim(index, escrow, RESERVE).unwrap();
+            assert_eq!(amount, expected);
+            escrow -= amount;
+            assert!(tournament.outstanding());
+        }
+
+        // The last claim also takes the rounding dust; the reserve stays for
+        // close_tournament
+        let amount = tournament.claim(3, escrow, RESERVE).unwrap();
+        assert_eq!(amount, 196_000 + 1);
+        assert_eq!(escrow - amount, RESERVE);
+        assert!(!tournament.outstanding());
+    }
+
+    #[test]
+    fn unpaid_placements_are_not_outstanding() {
+        let mut tournament = completed();
+        tournament.prize_splits = vec![10_000];
+        let escrow = tournament.prize_pool + RESERVE;
+
+        assert_eq!(tournament.claim(1, escrow, RESERVE).unwrap(), 0);
+        assert_eq!(
+            tournament.claim(0, escrow, RESERVE).unwrap(),
+            tournament.prize_pool
+        );
+        assert!(!tournament.outstanding());
+    }
+
+    #[test]
+    fn cancelled_tournaments_refund_entry_fees() {
+        let mut tournament = completed();
+        tournament.status = TournamentStatus::Cancelled;
+        let mut escrow = 4 * tournament.entry_fee + RESERVE;
+
+        for index in 0..3 {
+            escrow -= tournament.claim(index, escrow, RESERVE).unwrap();
+        }
+        assert_eq!(escrow, tournament.entry_fee + RESERVE);
+        assert_eq!(
+            tournament.claim(3, escrow, RESERVE).unwrap(),
+            tournament.entry_fee
+        );
+    }
+
+    #[test]
+    fn payouts_are_claimed_once() {
+        let mut tournament = completed();
+        tournament.claim(1, 0, 0).unwrap();
+        assert_eq!(
+            tournament.claim(1, 0, 0).unwrap_err(),
+            ErrorCode::PayoutAlreadyClaimed.into()
+        );
+    }
+
+    #[test]
+    fn live_matches_leave_the_active_game_limit_free() {
+        let mut player_state = PlayerState {
+            player: Pubkey::new_unique(),
+            active_game_ids: Vec::new(),
+            match_game_ids: Vec::new(),
+        };
+        player_state.add_match(7);
+
+        player_state.add_game(8, 1).unwrap();
+        assert_eq!(
+            player_state.add_game(9, 1).unwrap_err(),
+            ErrorCode::PlayerAlreadyInGame.into()
+        );
+
+        player_state.remove_game(8);
+        assert!(player_state.has_games());
+        player_state.remove_game(7);
+        assert!(!player_state.has_games());
+    }
+}
@@ -1,11 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 declare_id!("Gf6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnT");
 
 const PLATFORM_FEE_BPS: u16 = 100; // 1% platform fee for tips
 const MINIMUM_TIP: u64 = 1_000_000; // 0.001 SOL minimum tip
 
+// State.paused bits, one per instruction class
+const PAUSE_TIPS: u8 = 1 << 0; // send_tip
+const PAUSE_ALL: u8 = PAUSE_TIPS;
+
 #[program]
 pub mod korus_tipping {
     use super::*;
@@ -17,6 +24,8 @@ pub mod korus_tipping {
         state.total_tips = 0;
         state.total_volume = 0;
         state.platform_fee_bps = PLATFORM_FEE_BPS;
+        state.pending_authority = Pubkey::default();
+        state.paused = 0;
         Ok(())
     }
 
@@ -25,6 +34,7 @@ pub mod korus_tipping {
         amount: u64,
         post_id: String,
     ) -> Result<()> {
+        require!(ctx.accounts.state.paused & PAUSE_TIPS == 0, ErrorCode::ProgramPaused);
         require!(amount >= MINIMUM_TIP, ErrorCode::TipTooSmall);
         require!(post_id.len() <= 64, ErrorCode::PostIdTooLong);
         require!(
@@ -37,28 +47,38 @@ pub mod korus_tipping {
         let clock = Clock::get()?;
 
         // Calculate fees
-        let platform_fee = (amount * state.platform_fee_bps as u64) / 10000;
-        let recipient_amount = amount - platform_fee;
+        let fee_sent = (amount * state.platform_fee_bps as u64) / 10000;
+        let decimals = ctx.accounts.mint.decimals;
 
-        // Transfer platform fee to treasury
-        let cpi_accounts = Transfer {
+        // Transfer platform fee to treasury. Token-2022 mints with a transfer
+        // fee withhold part of every transfer, so balances are measured around
+        // each one to record what actually arrived.
+        let treasury_before = ctx.accounts.treasury_ata.amount;
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.sender_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.treasury_ata.to_account_info(),
             authority: ctx.accounts.sender.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, platform_fee)?;
+        token_interface::transfer_checked(cpi_ctx, fee_sent, decimals)?;
+        ctx.accounts.treasury_ata.reload()?;
+        let platform_fee = ctx.accounts.treasury_ata.amount - treasury_before;
 
         // Transfer tip to recipient
-        let cpi_accounts = Transfer {
+        let recipient_before = ctx.accounts.recipient_ata.amount;
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.sender_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.recipient_ata.to_account_info(),
             authority: ctx.accounts.sender.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, recipient_amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount - fee_sent, decimals)?;
+        ctx.accounts.recipient_ata.reload()?;
+        let recipient_amount = ctx.accounts.recipient_ata.amount - recipient_before;
 
         // Record the tip
         tip_record.sender = ctx.accounts.sender.key();
@@ -86,6 +106,7 @@ pub mod korus_tipping {
             amount,
             recipient_amount,
             platform_fee,
+            transfer_fee: amount - recipient_amount - platform_fee,
             post_id,
         });
 
@@ -105,6 +126,121 @@ pub mod korus_tipping {
         
         Ok(())
     }
+
+    pub fn set_pause(ctx: Context<UpdateConfig>, paused: u8) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        let state = &mut ctx.accounts.state;
+        let old_paused = state.paused;
+        state.paused = paused;
+
+        emit!(PauseUpdated {
+            old_paused,
+            new_paused: paused,
+        });
+
+        Ok(())
+    }
+
+    // Fees are paid to the treasury wallet's canonical ATA for the tipped mint,
+    // so the new wallet must already have one
+    pub fn update_treasury(ctx: Context<UpdateTreasury>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let old_treasury = state.treasury;
+        state.treasury = ctx.accounts.new_treasury.key();
+
+        emit!(TreasuryUpdated {
+            old_treasury,
+            new_treasury: state.treasury,
+        });
+
+        Ok(())
+    }
+
+    // First step of an authority transfer; nothing changes until the new key
+    // signs accept_authority
+    pub fn propose_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
+
+        let state = &mut ctx.accounts.state;
+        state.pending_authority = new_authority;
+
+        emit!(AuthorityTransferProposed {
+            authority: state.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let old_authority = state.authority;
+        state.authority = state.pending_authority;
+        state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            old_authority,
+            new_authority: state.authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_authority_transfer(ctx: Context<TransferAuthority>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require!(
+            state.pending_authority != Pubkey::default(),
+            ErrorCode::NoPendingAuthority
+        );
+        let pending_authority = state.pending_authority;
+        state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferCancelled {
+            authority: state.authority,
+            pending_authority,
+        });
+
+        Ok(())
+    }
+
+    // Grows a State account created before pending_authority and paused existed
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        {
+            let data = state_info.try_borrow_data()?;
+            require!(data.len() == 8 + LEGACY_STATE_LEN, ErrorCode::StateAlreadyMigrated);
+            require!(data[..8] == State::DISCRIMINATOR, ErrorCode::InvalidStateAccount);
+            // Authority is the first field in every State layout
+            require!(
+                data[8..40] == ctx.accounts.authority.key().to_bytes(),
+                ErrorCode::Unauthorized
+            );
+        }
+
+        let new_len = 8 + State::LEN;
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(state_info.lamports());
+        if shortfall > 0 {
+            let ix = system_instruction::transfer(
+                &ctx.accounts.authority.key(),
+                &state_info.key(),
+                shortfall,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    state_info.clone(),
+                ],
+            )?;
+        }
+        // The zero-filled tail decodes as no pending authority and unpaused
+        state_info.realloc(new_len, true)?;
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -127,7 +263,7 @@ pub struct Initialize<'info> {
 #[derive(Accounts)]
 #[instruction(amount: u64, post_id: String)]
 pub struct SendTip<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"state"], bump)]
     pub state: Account<'info, State>,
     
     #[account(
@@ -167,14 +303,28 @@ pub struct SendTip<'info> {
     /// CHECK: Recipient account
     pub recipient: UncheckedAccount<'info>,
     
-    #[account(mut)]
-    pub sender_ata: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub recipient_ata: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub treasury_ata: Account<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub sender_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = treasury_ata.owner == state.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -182,12 +332,83 @@ pub struct SendTip<'info> {
 pub struct UpdatePlatformFee<'info> {
     #[account(
         mut,
+        seeds = [b"state"],
+        bump,
         has_one = authority,
     )]
     pub state: Account<'info, State>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority,
+    )]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority,
+    )]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+    /// CHECK: New treasury wallet, validated through its token account
+    pub new_treasury: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = new_treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        has_one = authority,
+    )]
+    pub state: Account<'info, State>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump,
+        constraint = state.pending_authority == pending_authority.key() @ ErrorCode::NotPendingAuthority,
+    )]
+    pub state: Account<'info, State>,
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Legacy State account, validated in the instruction
+    #[account(mut, seeds = [b"state"], bump, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct State {
     pub authority: Pubkey,
@@ -195,10 +416,15 @@ pub struct State {
     pub total_tips: u64,
     pub total_volume: u64,
     pub platform_fee_bps: u16,
+    pub pending_authority: Pubkey, // Default when no transfer is in progress
+    pub paused: u8,                // PAUSE_* bits
 }
 
+// Size of State as first deployed, before migrate_state
+const LEGACY_STATE_LEN: usize = 32 + 32 + 8 + 8 + 2;
+
 impl State {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 2;
+    pub const LEN: usize = LEGACY_STATE_LEN + 32 + 1;
 }
 
 #[account]
@@ -231,8 +457,9 @@ pub struct TipSent {
     pub sender: Pubkey,
     pub recipient: Pubkey,
     pub amount: u64,
-    pub recipient_amount: u64,
-    pub platform_fee: u64,
+    pub recipient_amount: u64, // Received by the recipient after any transfer fee
+    pub platform_fee: u64,     // Received by the treasury after any transfer fee
+    pub transfer_fee: u64,     // Withheld by the mint's transfer-fee extension
     pub post_id: String,
 }
 
@@ -242,6 +469,36 @@ pub struct PlatformFeeUpdated {
     pub new_fee: u16,
 }
 
+#[event]
+pub struct PauseUpdated {
+    pub old_paused: u8,
+    pub new_paused: u8,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Tip amount is too small")]
@@ -252,4 +509,22 @@ pub enum ErrorCode {
     CannotTipSelf,
     #[msg("Platform fee is too high")]
     FeeTooHigh,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Unauthorized caller")]
+    Unauthorized,
+    #[msg("State account already uses the current layout")]
+    StateAlreadyMigrated,
+    #[msg("Not a valid state account")]
+    InvalidStateAccount,
+    #[msg("Treasury token account does not belong to the treasury")]
+    InvalidTreasury,
+    #[msg("This instruction is paused")]
+    ProgramPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
 }
\ No newline at end of file
@@ -0,0 +1,294 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { KorusGameEscrow } from "../target/types/korus_game_escrow";
+import { assert } from "chai";
+
+// Games created with off-chain rules are settled by a reported result rather
+// than by moves, through each of the reporting paths
+describe("off-chain rules settlement", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.KorusGameEscrow as Program<KorusGameEscrow>;
+  const { SystemProgram, PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const authority = provider.wallet.publicKey;
+  const wager = new anchor.BN(LAMPORTS_PER_SOL / 10);
+
+  let statePda: anchor.web3.PublicKey;
+  let gameTypeConfigPda: anchor.web3.PublicKey;
+  let treasury: anchor.web3.PublicKey;
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  // SOL games leave every token account out
+  const noTokens = {
+    mint: null,
+    vault: null,
+    treasuryTokenAccount: null,
+    player1TokenAccount: null,
+    player2TokenAccount: null,
+    tokenProgram: null,
+  };
+
+  // Creates and joins an off-chain game between two fresh players (one
+  // active game each is the default limit), returning the settlement accounts
+  const startOffChainGame = async () => {
+    const player1 = Keypair.generate();
+    const player2 = Keypair.generate();
+    for (const keypair of [player1, player2]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL)
+      );
+    }
+
+    const state = await program.account.state.fetch(statePda);
+    const game = pda(Buffer.from("game"), state.totalGames.toArrayLike(Buffer, "le", 8));
+    const escrow = pda(Buffer.from("escrow"), game.toBuffer());
+    const betPool = pda(Buffer.from("bet_pool"), game.toBuffer());
+    const player1State = pda(Buffer.from("player"), player1.publicKey.toBuffer());
+    const player2State = pda(Buffer.from("player"), player2.publicKey.toBuffer());
+
+    await program.methods
+      .createGameWithDeposit({ ticTacToe: {} }, wager, 1, true, null, null)
+      .accountsStrict({
+        state: statePda,
+        gameTypeConfig: gameTypeConfigPda,
+        game,
+        playerState: player1State,
+        escrow,
+        player1: player1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([player1])
+      .rpc();
+
+    await program.methods
+      .joinGame()
+      .accountsStrict({
+        state: statePda,
+        game,
+        player2State,
+        escrow,
+        betPool,
+        bet: pda(Buffer.from("bet"), betPool.toBuffer(), player2.publicKey.toBuffer()),
+        player2: player2.publicKey,
+        joinSigner: null,
+        systemProgram: SystemProgram.programId,
+        tokenEscrow: noTokens,
+      })
+      .signers([player2])
+      .rpc();
+
+    return {
+      player1,
+      player2,
+      accounts: {
+        tokenEscrow: noTokens,
+        state: statePda,
+        game,
+        player1State,
+        player2State,
+        escrow,
+        treasury,
+        player1: player1.publicKey,
+        player2: player2.publicKey,
+      },
+    };
+  };
+
+  const assertWonBy = async (game: anchor.web3.PublicKey, winner: anchor.web3.PublicKey) => {
+    const settled = await program.account.game.fetch(game);
+    assert.deepEqual(settled.status, { completed: {} });
+    assert.equal(settled.winner.toBase58(), winner.toBase58());
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, code, err.toString());
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  before(async () => {
+    statePda = pda(Buffer.from("state"));
+    gameTypeConfigPda = pda(Buffer.from("game_type"), Buffer.from([0]));
+
+    if (!(await provider.connection.getAccountInfo(statePda))) {
+      await program.methods
+        .initialize(Keypair.generate().publicKey)
+        .accountsStrict({
+          state: statePda,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+    if (!(await provider.connection.getAccountInfo(gameTypeConfigPda))) {
+      await program.methods
+        .initGameTypeConfig({ ticTacToe: {} })
+        .accountsStrict({
+          state: statePda,
+          gameTypeConfig: gameTypeConfigPda,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    treasury = (await program.account.state.fetch(statePda)).treasury;
+  });
+
+  it("settles a new game once the oracle threshold agrees", async () => {
+    const { player1, accounts } = await startOffChainGame();
+    const oracleSet = pda(Buffer.from("oracle_set"));
+
+    const setOracleSet = (minWager: anchor.BN) =>
+      program.methods
+        .setOracleSet([authority], 1, minWager)
+        .accountsStrict({
+          state: statePda,
+          oracleSet,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // A single-oracle set covering this game's wager
+    await setOracleSet(wager);
+
+    await program.methods
+      .submitResult(player1.publicKey)
+      .accountsStrict({
+        ...accounts,
+        oracleSet,
+        attestations: pda(Buffer.from("attestations"), accounts.game.toBuffer()),
+        oracle: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await assertWonBy(accounts.game, player1.publicKey);
+
+    // Put the minimum out of reach so complete_game stays available to the
+    // other suites
+    await setOracleSet(new anchor.BN("18446744073709551615"));
+  });
+
+  describe("authority verdicts with a dispute window", () => {
+    const arbitrator = Keypair.generate();
+
+    const setDisputeWindow = (arbiter: anchor.web3.PublicKey, seconds: number) =>
+      program.methods
+        .setDisputeConfig(arbiter, new anchor.BN(seconds))
+        .accountsStrict({ state: statePda, authority })
+        .rpc();
+
+    const propose = (game: anchor.web3.PublicKey, winner: anchor.web3.PublicKey) =>
+      program.methods
+        .proposeResult(winner)
+        .accountsStrict({ state: statePda, game, authority })
+        .rpc();
+
+    before(async () => {
+      await setDisputeWindow(arbitrator.publicKey, 1);
+    });
+
+    // complete_game is refused while a window is configured. The arbitrator
+    // stays, as it can't be cleared once set.
+    after(async () => {
+      await setDisputeWindow(arbitrator.publicKey, 0);
+    });
+
+    it("pays out an undisputed proposal once the window passes", async () => {
+      const { player2, accounts } = await startOffChainGame();
+      await propose(accounts.game, player2.publicKey);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await program.methods
+        .finalizeResult()
+        .accountsStrict({ ...accounts, caller: authority })
+        .rpc();
+
+      await assertWonBy(accounts.game, player2.publicKey);
+    });
+
+    it("lets the arbitrator overrule a disputed proposal", async () => {
+      const { player1, player2, accounts } = await startOffChainGame();
+      await propose(accounts.game, player2.publicKey);
+
+      await program.methods
+        .disputeResult()
+        .accountsStrict({ game: accounts.game, player: player1.publicKey })
+        .signers([player1])
+        .rpc();
+      await program.methods
+        .resolveDispute(player1.publicKey)
+        .accountsStrict({ ...accounts, caller: arbitrator.publicKey })
+        .signers([arbitrator])
+        .rpc();
+
+      await assertWonBy(accounts.game, player1.publicKey);
+    });
+
+    // A dispute still open when the window is turned off would otherwise have
+    // nobody to resolve it
+    it("refuses to clear the arbitrator", async () => {
+      await expectError(setDisputeWindow(PublicKey.default, 0), "InvalidArbitrator");
+    });
+  });
+
+  describe("result vouchers", () => {
+    const resultSigner = Keypair.generate();
+
+    const setResultSigner = (signer: anchor.web3.PublicKey) =>
+      program.methods
+        .setResultSigner(signer)
+        .accountsStrict({ state: statePda, authority })
+        .rpc();
+
+    before(async () => {
+      await setResultSigner(resultSigner.publicKey);
+    });
+
+    after(async () => {
+      await setResultSigner(PublicKey.default);
+    });
+
+    it("settles a new game on a voucher from the result signer", async () => {
+      const { player2, accounts } = await startOffChainGame();
+      const { gameId } = await program.account.game.fetch(accounts.game);
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+
+      // signatures::result_message(VOUCHER_DOMAIN, ..) followed by the expiry
+      const message = Buffer.concat([
+        Buffer.from("korus-voucher"),
+        program.programId.toBuffer(),
+        gameId.toArrayLike(Buffer, "le", 8),
+        player2.publicKey.toBuffer(),
+        expiry.toArrayLike(Buffer, "le", 8),
+      ]);
+
+      await program.methods
+        .settleWithVoucher(player2.publicKey, expiry)
+        .accountsStrict({
+          ...accounts,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          caller: authority,
+        })
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: resultSigner.secretKey,
+            message,
+          }),
+        ])
+        .rpc();
+
+      await assertWonBy(accounts.game, player2.publicKey);
+    });
+  });
+});
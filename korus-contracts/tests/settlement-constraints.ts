@@ -0,0 +1,218 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { KorusGameEscrow } from "../target/types/korus_game_escrow";
+import { assert } from "chai";
+
+// Settlement contexts must reject treasury, player and state accounts that
+// don't belong to the game being settled
+describe("settlement account constraints", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.KorusGameEscrow as Program<KorusGameEscrow>;
+  const { SystemProgram, PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+  const player1 = Keypair.generate();
+  const player2 = Keypair.generate();
+  const attacker = Keypair.generate();
+  const wager = new anchor.BN(LAMPORTS_PER_SOL / 10);
+
+  let statePda: anchor.web3.PublicKey;
+  let gameTypeConfigPda: anchor.web3.PublicKey;
+  let treasury: anchor.web3.PublicKey;
+  let gamePda: anchor.web3.PublicKey;
+  let escrowPda: anchor.web3.PublicKey;
+  let player1StatePda: anchor.web3.PublicKey;
+  let player2StatePda: anchor.web3.PublicKey;
+
+  // A well-formed State owned by the program at another address, loaded into
+  // the test validator from tests/fixtures/forged-state.json (see Anchor.toml)
+  const forgedState = new PublicKey("34haeyudfKTXZ7ks4RnrRxpGAxVNM2cg74PV9Es1g8Vv");
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  // SOL games leave every token account out
+  const noTokens = {
+    mint: null,
+    vault: null,
+    treasuryTokenAccount: null,
+    player1TokenAccount: null,
+    player2TokenAccount: null,
+    tokenProgram: null,
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, code, err.toString());
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  // Accounts for complete_game / claim_timeout_win, with overrides for the
+  // account under test
+  const settlementAccounts = (overrides: Record<string, anchor.web3.PublicKey> = {}) => ({
+    tokenEscrow: noTokens,
+    state: statePda,
+    game: gamePda,
+    gameTypeConfig: gameTypeConfigPda,
+    player1State: player1StatePda,
+    player2State: player2StatePda,
+    escrow: escrowPda,
+    treasury,
+    player1: player1.publicKey,
+    player2: player2.publicKey,
+    ...overrides,
+  });
+
+  before(async () => {
+    for (const keypair of [player1, player2, attacker]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(keypair.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    }
+
+    statePda = pda(Buffer.from("state"));
+    gameTypeConfigPda = pda(Buffer.from("game_type"), Buffer.from([0]));
+    player1StatePda = pda(Buffer.from("player"), player1.publicKey.toBuffer());
+    player2StatePda = pda(Buffer.from("player"), player2.publicKey.toBuffer());
+
+    if (!(await provider.connection.getAccountInfo(statePda))) {
+      await program.methods
+        .initialize(Keypair.generate().publicKey)
+        .accountsStrict({
+          state: statePda,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+    if (!(await provider.connection.getAccountInfo(gameTypeConfigPda))) {
+      await program.methods
+        .initGameTypeConfig({ ticTacToe: {} })
+        .accountsStrict({
+          state: statePda,
+          gameTypeConfig: gameTypeConfigPda,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const state = await program.account.state.fetch(statePda);
+    treasury = state.treasury;
+    gamePda = pda(Buffer.from("game"), state.totalGames.toArrayLike(Buffer, "le", 8));
+    escrowPda = pda(Buffer.from("escrow"), gamePda.toBuffer());
+
+    await program.methods
+      .createGameWithDeposit({ ticTacToe: {} }, wager, 1, true, player2.publicKey, null)
+      .accountsStrict({
+        state: statePda,
+        gameTypeConfig: gameTypeConfigPda,
+        game: gamePda,
+        playerState: player1StatePda,
+        escrow: escrowPda,
+        player1: player1.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([player1])
+      .rpc();
+
+    const betPool = pda(Buffer.from("bet_pool"), gamePda.toBuffer());
+    await program.methods
+      .joinGame()
+      .accountsStrict({
+        state: statePda,
+        game: gamePda,
+        player2State: player2StatePda,
+        escrow: escrowPda,
+        betPool,
+        bet: pda(Buffer.from("bet"), betPool.toBuffer(), player2.publicKey.toBuffer()),
+        player2: player2.publicKey,
+        joinSigner: null,
+        systemProgram: SystemProgram.programId,
+        tokenEscrow: noTokens,
+      })
+      .signers([player2])
+      .rpc();
+  });
+
+  describe("claim_timeout_win", () => {
+    const claim = (overrides: Record<string, anchor.web3.PublicKey>) =>
+      program.methods
+        .claimTimeoutWin()
+        .accountsStrict({ ...settlementAccounts(overrides), claimer: player1.publicKey })
+        .signers([player1])
+        .rpc();
+
+    it("rejects a treasury other than State.treasury", async () => {
+      await expectError(claim({ treasury: attacker.publicKey }), "InvalidTreasury");
+    });
+
+    it("rejects a player1 account other than Game.player1", async () => {
+      await expectError(claim({ player1: attacker.publicKey }), "NotAPlayer");
+    });
+
+    it("rejects swapped player accounts", async () => {
+      await expectError(
+        claim({ player1: player2.publicKey, player2: player1.publicKey }),
+        "NotAPlayer"
+      );
+    });
+
+    it("rejects a State account that isn't the state PDA", async () => {
+      await expectError(claim({ state: forgedState }), "ConstraintSeeds");
+    });
+  });
+
+  describe("complete_game", () => {
+    const complete = (overrides: Record<string, anchor.web3.PublicKey>) => {
+      // The fee comes from the game itself, so there's no game type config
+      const { gameTypeConfig, ...accounts } = settlementAccounts(overrides);
+      return program.methods
+        .completeGame(player1.publicKey)
+        .accountsStrict({ ...accounts, authority: provider.wallet.publicKey })
+        .rpc();
+    };
+
+    it("rejects a treasury other than State.treasury", async () => {
+      await expectError(complete({ treasury: attacker.publicKey }), "InvalidTreasury");
+    });
+
+    it("rejects a player2 account other than Game.player2", async () => {
+      await expectError(complete({ player2: attacker.publicKey }), "NotAPlayer");
+    });
+
+    it("rejects swapped player accounts", async () => {
+      await expectError(
+        complete({ player1: player2.publicKey, player2: player1.publicKey }),
+        "NotAPlayer"
+      );
+    });
+  });
+
+  describe("authority_cancel_expired_game", () => {
+    it("rejects a refund account other than Game.player1", async () => {
+      await expectError(
+        program.methods
+          .authorityCancelExpiredGame()
+          .accountsStrict({
+            state: statePda,
+            game: gamePda,
+            gameTypeConfig: gameTypeConfigPda,
+            playerState: player1StatePda,
+            escrow: escrowPda,
+            player1: attacker.publicKey,
+            authority: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenEscrow: noTokens,
+          })
+          .rpc(),
+        "NotGameCreator"
+      );
+    });
+  });
+});
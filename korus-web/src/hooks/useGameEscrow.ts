@@ -14,6 +14,13 @@ const GAME_ESCROW_PROGRAM_ID = new PublicKey(
   process.env.NEXT_PUBLIC_GAME_ESCROW_PROGRAM_ID || '4iUdAkPRmZLzUFXTLpt5QPGmUUtP6yfgpPpF3sLD9xtd'
 );
 
+// Anchor reads the program ID in an optional account's slot as "not provided"
+const NO_ACCOUNT = { pubkey: GAME_ESCROW_PROGRAM_ID, isSigner: false, isWritable: false };
+
+// SOL games leave out every account of the TokenEscrow group:
+// mint, vault, treasury / player1 / player2 token accounts, token program
+const NO_TOKEN_ESCROW = Array(6).fill(NO_ACCOUNT);
+
 // Helper to call RPC via server-side proxy (uses correct mainnet endpoint)
 async function rpcCall(method: string, params: unknown[]) {
   const response = await fetch('/api/rpc', {
@@ -146,6 +153,11 @@ export function useGameEscrow() {
         GAME_ESCROW_PROGRAM_ID
       );
 
+      const [gameTypeConfigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('game_type'), Buffer.from([gameTypeValue])],
+        GAME_ESCROW_PROGRAM_ID
+      );
+
       logger.log('Derived PDAs:', {
         wallet: publicKey.toString(),
         statePda: statePda.toString(),
@@ -159,20 +171,27 @@ export function useGameEscrow() {
       // Instruction discriminator: SHA256("global:create_game")[:8]
       const discriminator = Buffer.from([124, 69, 75, 66, 184, 220, 72, 206]);
 
-      // Encode game_type (u8) and wager_amount (u64, little-endian)
+      // Encode game_type (u8 enum), wager_amount (u64, little-endian), then
+      // rounds_to_win (u8), off_chain_rules (bool), invited_player and
+      // join_key (Option<Pubkey>, None for an open game)
       const gameTypeBuffer = Buffer.alloc(1);
       gameTypeBuffer.writeUInt8(gameTypeValue, 0);
 
       const wagerBuffer = Buffer.alloc(8);
       wagerBuffer.writeBigUInt64LE(BigInt(wagerLamports), 0);
 
-      const instructionData = Buffer.concat([discriminator, gameTypeBuffer, wagerBuffer]);
+      // A single round played through the backend, which reports the result
+      // with complete_game
+      const settingsBuffer = Buffer.from([1, 1, 0, 0]);
+
+      const instructionData = Buffer.concat([discriminator, gameTypeBuffer, wagerBuffer, settingsBuffer]);
 
       // Build the create_game instruction
       const createGameIx = new TransactionInstruction({
         programId: GAME_ESCROW_PROGRAM_ID,
         keys: [
           { pubkey: statePda, isSigner: false, isWritable: true },
+          { pubkey: gameTypeConfigPda, isSigner: false, isWritable: false },
           { pubkey: gamePda, isSigner: false, isWritable: true },
           { pubkey: playerStatePda, isSigner: false, isWritable: true },
           { pubkey: escrowPda, isSigner: false, isWritable: true },
@@ -310,11 +329,24 @@ export function useGameEscrow() {
         GAME_ESCROW_PROGRAM_ID
       );
 
+      // join_game checks the joining wallet holds no bet on the game
+      const [betPoolPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('bet_pool'), gamePda.toBuffer()],
+        GAME_ESCROW_PROGRAM_ID
+      );
+
+      const [betPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('bet'), betPoolPda.toBuffer(), publicKey.toBuffer()],
+        GAME_ESCROW_PROGRAM_ID
+      );
+
       logger.log('Derived PDAs:', {
         gamePda: gamePda.toString(),
         playerStatePda: playerStatePda.toString(),
         escrowPda: escrowPda.toString(),
         statePda: statePda.toString(),
+        betPoolPda: betPoolPda.toString(),
+        betPda: betPda.toString(),
       });
 
       // Get game account to read wager
@@ -327,8 +359,8 @@ export function useGameEscrow() {
       }
       const gameData = Buffer.from(gameAccountResult.value.data[0], 'base64');
 
-      // Read wager from game account (discriminator(8) + game_id(8) + game_type(1) + player1(32) + player2_option(1+32) + wager(8))
-      const wagerOffset = 8 + 8 + 1 + 32 + 1 + 32;
+      // Read wager from game account (discriminator(8) + game_id(8) + game_type(1) + player1(32) + player2(32) + wager(8))
+      const wagerOffset = 8 + 8 + 1 + 32 + 32;
       const wagerLamports = Number(gameData.readBigUInt64LE(wagerOffset));
       logger.log('Game wager:', wagerLamports, 'lamports');
 
@@ -343,8 +375,12 @@ export function useGameEscrow() {
           { pubkey: gamePda, isSigner: false, isWritable: true },
           { pubkey: playerStatePda, isSigner: false, isWritable: true },
           { pubkey: escrowPda, isSigner: false, isWritable: true },
+          { pubkey: betPoolPda, isSigner: false, isWritable: false },
+          { pubkey: betPda, isSigner: false, isWritable: false },
           { pubkey: publicKey, isSigner: true, isWritable: true },
+          NO_ACCOUNT, // join_signer: open games have no join code
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ...NO_TOKEN_ESCROW,
         ],
         data: discriminator,
       });
@@ -480,6 +516,7 @@ export function useGameEscrow() {
           { pubkey: escrowPda, isSigner: false, isWritable: true },
           { pubkey: publicKey, isSigner: true, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ...NO_TOKEN_ESCROW,
         ],
         data: discriminator,
       });